@@ -0,0 +1,64 @@
+"""
+生成 Templates/rust-bin 下的 mod.sbn 和 toml.sbn：
+在 Luban 自带的 rust-bin 模板里插入 Templates/godot 中的片段，让 bean 和枚举实现 ToGodot/FromGodot
+godot 的版本从 rust/Cargo.toml 读取，生成的 crate 与游戏使用同一个版本
+Luban 的模板不支持 include，所以只能在生成前拼接，gen.bat 会先运行这个脚本
+"""
+
+import sys
+import tomllib
+from pathlib import Path
+
+DATA = Path(__file__).resolve().parent.parent
+UPSTREAM = DATA / "Tools" / "Luban" / "Templates" / "rust-bin"
+FRAGMENTS = DATA / "Templates" / "godot"
+OUTPUT = DATA / "Templates" / "rust-bin"
+CARGO_TOML = DATA.parent / "rust" / "Cargo.toml"
+
+# (片段, 上游模板里的位置, 插在位置之前还是之后)
+MOD_FRAGMENTS = [
+    ("functions.sbn", "~}}\n{{~ for mod in __mod.sub_mods ~}}\n", "before"),
+    ("use.sbn", "use luban_lib::*;\n", "after"),
+    ("enum.sbn", "{{~end~}}\n{{~ for bean in __mod.beans", "before"),
+    ("bean.sbn", "    pub const __ID__: i32 = {{bean.id}};\n}\n{{~end~}}\n", "after"),
+]
+TOML_ANCHOR = 'luban_lib = { path = "../luban_lib" }\n'
+
+
+def read(path):
+    return path.read_text(encoding="utf-8-sig")
+
+
+def insert(template, fragment, anchor, where):
+    if template.count(anchor) != 1:
+        sys.exit(f"rust-bin/mod.sbn 里找不到唯一的位置插入 {fragment}，Luban 的模板可能更新了")
+    index = template.index(anchor)
+    if where == "after":
+        index += len(anchor)
+    return template[:index] + read(FRAGMENTS / fragment) + template[index:]
+
+
+def godot_version():
+    with CARGO_TOML.open("rb") as file:
+        godot = tomllib.load(file)["dependencies"]["godot"]
+    return godot if isinstance(godot, str) else godot["version"]
+
+
+def main():
+    template = read(UPSTREAM / "mod.sbn")
+    for fragment, anchor, where in MOD_FRAGMENTS:
+        template = insert(template, fragment, anchor, where)
+
+    toml = read(UPSTREAM / "toml.sbn")
+    if TOML_ANCHOR not in toml:
+        sys.exit("rust-bin/toml.sbn 里找不到 luban_lib 依赖，Luban 的模板可能更新了")
+    godot = f'godot = "{godot_version()}"\n'
+    toml = toml.replace(TOML_ANCHOR, TOML_ANCHOR + godot)
+
+    OUTPUT.mkdir(parents=True, exist_ok=True)
+    (OUTPUT / "mod.sbn").write_text(template, encoding="utf-8", newline="\n")
+    (OUTPUT / "toml.sbn").write_text(toml, encoding="utf-8", newline="\n")
+
+
+if __name__ == "__main__":
+    main()
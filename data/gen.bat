@@ -3,9 +3,13 @@ set WORKSPACE=.
 set LUBAN_DLL=%WORKSPACE%\Tools\Luban\Luban.dll
 set CONF_ROOT=%WORKSPACE%
 
+rem 在 Luban 自带的 rust-bin 模板里插入 Godot 转换，生成 Templates\rust-bin
+python %WORKSPACE%\Tools\godot_templates.py || exit /b 1
+
 dotnet %LUBAN_DLL% ^
     -t all ^
     -c rust-bin ^
+    --customTemplateDir %WORKSPACE%\Templates ^
     -d bin ^
     -d json ^
     --conf %CONF_ROOT%\luban.conf ^
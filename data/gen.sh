@@ -0,0 +1,25 @@
+#!/bin/bash
+# 与 gen.bat 相同，供 Linux 和 CI 使用
+set -e
+cd "$(dirname "$0")"
+
+WORKSPACE=.
+LUBAN_DLL=$WORKSPACE/Tools/Luban/Luban.dll
+CONF_ROOT=$WORKSPACE
+
+python3 $WORKSPACE/Tools/godot_templates.py
+
+dotnet $LUBAN_DLL \
+    -t all \
+    -c rust-bin \
+    --customTemplateDir $WORKSPACE/Templates \
+    -d bin \
+    -d json \
+    --conf $CONF_ROOT/luban.conf \
+    -x outputCodeDir=../rust/gen \
+    -x outputDataDir=../godot/data/bytes \
+    -x json.outputDataDir=$WORKSPACE/json \
+    -x pathValidator.rootDir=../godot \
+    -x l10n.provider=default \
+    -x l10n.textFile.path=Sheet1@$WORKSPACE/Datas/#translate.xlsx \
+    -x l10n.textFile.keyFieldName=key
@@ -0,0 +1,3 @@
+//! 占位：运行 data/gen.bat 后由 Luban 生成的代码覆盖
+
+compile_error!("rust/gen/cfg has not been generated yet, run data/gen.bat before building with the config feature");
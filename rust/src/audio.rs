@@ -0,0 +1,237 @@
+/*!
+程序生成的音效，不需要任何音频文件，用 AudioStreamGenerator 现场合成
+零件事件走 cue 总线，胜利/失败的提示音走 stinger 总线，无头模式下不发声
+*/
+
+use crate::game_signals::GameSignals;
+use crate::parts::feedback::DangerFeedback;
+use crate::parts::{BlueButton, PinkButton, RedButton, YellowButton};
+use crate::settings::Settings;
+use godot::classes::{
+    AudioServer, AudioStreamGenerator, AudioStreamGeneratorPlayback, AudioStreamPlayer,
+    DisplayServer, INode,
+};
+use godot::prelude::*;
+use log::{error, info};
+use std::f64::consts::TAU;
+
+/// Sample rate of the generated streams
+const MIX_RATE: f64 = 22050.0;
+/// Seconds of audio each generator can hold, the longest stinger must fit
+const BUFFER_LENGTH: f32 = 1.5;
+
+/// One synthesized note, a frequency of 0 is a rest
+#[derive(Clone, Copy)]
+struct Tone {
+    frequency: f64,
+    duration: f64,
+    volume: f64,
+}
+
+impl Tone {
+    const fn new(frequency: f64, duration: f64, volume: f64) -> Self {
+        Self {
+            frequency,
+            duration,
+            volume,
+        }
+    }
+
+    /// Append the samples of this tone, a short attack and a linear fade out avoid clicks
+    fn render(&self, frames: &mut PackedVector2Array) {
+        let count = (self.duration * MIX_RATE) as usize;
+        let attack = (0.005 * MIX_RATE) as usize;
+        for i in 0..count {
+            let envelope = (i as f64 / attack as f64).min(1.0) * (1.0 - i as f64 / count as f64);
+            let t = i as f64 / MIX_RATE;
+            let sample = (TAU * self.frequency * t).sin() * self.volume * envelope;
+            frames.push(Vector2::new(sample as f32, sample as f32));
+        }
+    }
+}
+
+const RED_TICK: [Tone; 1] = [Tone::new(880.0, 0.05, 0.4)];
+const YELLOW_CHIME: [Tone; 2] = [Tone::new(660.0, 0.12, 0.4), Tone::new(990.0, 0.2, 0.4)];
+const ALARM: [Tone; 2] = [Tone::new(1200.0, 0.06, 0.3), Tone::new(900.0, 0.06, 0.3)];
+const VICTORY: [Tone; 4] = [
+    Tone::new(523.25, 0.15, 0.5),
+    Tone::new(659.25, 0.15, 0.5),
+    Tone::new(783.99, 0.15, 0.5),
+    Tone::new(1046.5, 0.4, 0.5),
+];
+const FAILURE: [Tone; 3] = [
+    Tone::new(392.0, 0.2, 0.5),
+    Tone::new(311.13, 0.2, 0.5),
+    Tone::new(246.94, 0.5, 0.5),
+];
+
+/// AudioCues plays generated sounds for part events and the end of a run
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct AudioCues {
+    base: Base<Node>,
+    /// Node whose children are the parts to listen to
+    #[export]
+    parts: Option<Gd<Node>>,
+    /// Bus for the part cues
+    #[export]
+    #[init(val = Settings::SFX_BUS.into())]
+    cue_bus: StringName,
+    /// Bus for the victory and failure stingers
+    #[export]
+    #[init(val = Settings::SFX_BUS.into())]
+    stinger_bus: StringName,
+    /// Blue power at or below which the warning beep plays
+    #[export]
+    #[init(val = 25.0)]
+    blue_low_power: f64,
+    /// Blue power at or above which the warning beep plays
+    #[export]
+    #[init(val = 75.0)]
+    blue_high_power: f64,
+    cue_player: Option<Gd<AudioStreamPlayer>>,
+    stinger_player: Option<Gd<AudioStreamPlayer>>,
+}
+
+#[godot_api]
+impl INode for AudioCues {
+    fn ready(&mut self) {
+        if DisplayServer::singleton().get_name() == GString::from("headless") {
+            info!("headless display, audio cues muted");
+            return;
+        }
+
+        let cue_bus = self.cue_bus.clone();
+        let stinger_bus = self.stinger_bus.clone();
+        self.cue_player = Some(self.create_player(&cue_bus));
+        self.stinger_player = Some(self.create_player(&stinger_bus));
+
+        GameSignals::singleton()
+            .signals()
+            .countdown_started()
+            .connect_other(self, Self::connect_parts);
+        GameSignals::singleton()
+            .signals()
+            .game_victory()
+            .connect_other(self, |this| Self::play(&this.stinger_player, &VICTORY));
+        GameSignals::singleton()
+            .signals()
+            .game_failure()
+            .connect_other(self, |this, _reason| {
+                Self::play(&this.stinger_player, &FAILURE)
+            });
+    }
+}
+
+#[godot_api]
+impl AudioCues {
+    #[func]
+    fn on_red_window_opened(&mut self) {
+        Self::play(&self.cue_player, &RED_TICK);
+    }
+
+    #[func]
+    fn on_yellow_window_opened(&mut self) {
+        Self::play(&self.cue_player, &YELLOW_CHIME);
+    }
+
+    /// Beep while the power is near either end, higher power gives a higher pitch
+    #[func]
+    fn on_blue_power_changed(&mut self, power: f64) {
+        if power > self.blue_low_power && power < self.blue_high_power {
+            return;
+        }
+        Self::play(
+            &self.cue_player,
+            &[Tone::new(200.0 + power * 8.0, 0.08, 0.35)],
+        );
+    }
+
+    #[func]
+    fn on_danger_critical(&mut self) {
+        Self::play(&self.cue_player, &ALARM);
+    }
+
+    #[func]
+    fn on_pink_gear_turned(&mut self, clockwise: bool) {
+        let frequency = if clockwise { 1500.0 } else { 1200.0 };
+        Self::play(&self.cue_player, &[Tone::new(frequency, 0.02, 0.5)]);
+    }
+
+    /// Listen to the parts of the level, they are replaced whenever a level is loaded
+    fn connect_parts(&mut self) {
+        let parts = self
+            .parts
+            .as_ref()
+            .map(|parts| parts.get_children())
+            .unwrap_or_default();
+        for mut child in parts.iter_shared() {
+            let (signal, method) = if child.clone().try_cast::<RedButton>().is_ok() {
+                ("window_opened", "on_red_window_opened")
+            } else if child.clone().try_cast::<YellowButton>().is_ok() {
+                ("window_opened", "on_yellow_window_opened")
+            } else if child.clone().try_cast::<BlueButton>().is_ok() {
+                ("power_changed", "on_blue_power_changed")
+            } else if child.clone().try_cast::<PinkButton>().is_ok() {
+                ("gear_turned", "on_pink_gear_turned")
+            } else {
+                continue;
+            };
+            let callable = self.to_gd().callable(method);
+            if !child.is_connected(signal, &callable) {
+                child.connect(signal, &callable);
+            }
+
+            let Some(mut feedback) = child.try_get_node_as::<DangerFeedback>("%DangerFeedback")
+            else {
+                continue;
+            };
+            let on_danger_critical = self.to_gd().callable("on_danger_critical");
+            if feedback.bind().audio_alert
+                && !feedback.is_connected("critical_entered", &on_danger_critical)
+            {
+                feedback.connect("critical_entered", &on_danger_critical);
+            }
+        }
+    }
+
+    fn create_player(&mut self, bus: &StringName) -> Gd<AudioStreamPlayer> {
+        if AudioServer::singleton().get_bus_index(bus) < 0 {
+            error!("Audio bus {} not found, falling back to Master", bus);
+        }
+
+        let mut stream = AudioStreamGenerator::new_gd();
+        stream.set_mix_rate(MIX_RATE as f32);
+        stream.set_buffer_length(BUFFER_LENGTH);
+
+        let mut player = AudioStreamPlayer::new_alloc();
+        player.set_stream(&stream);
+        player.set_bus(bus);
+        self.base_mut().add_child(&player);
+        player.play();
+        player
+    }
+
+    /// Queue the tones on a generator, whatever does not fit in its buffer is dropped
+    fn play(player: &Option<Gd<AudioStreamPlayer>>, tones: &[Tone]) {
+        let Some(playback) = player
+            .clone()
+            .and_then(|mut player| player.get_stream_playback())
+        else {
+            return;
+        };
+        let Ok(mut playback) = playback.try_cast::<AudioStreamGeneratorPlayback>() else {
+            return;
+        };
+
+        let mut frames = PackedVector2Array::new();
+        for tone in tones {
+            tone.render(&mut frames);
+        }
+        let available = playback.get_frames_available() as usize;
+        if frames.len() > available {
+            frames.resize(available);
+        }
+        playback.push_buffer(&frames);
+    }
+}
@@ -0,0 +1,355 @@
+/*!
+卡片：定义在 `card.TbCardsInfo`，效果在 `card.TbCardEffects`，每行是 (卡片名, 效果名, 数值)
+没有效果的卡片（矿物、材料）不会出现在对局里
+一局开始时，手上卡片的效果通过 `Modifiers` 改写零件属性，对局结束时恢复原值
+没有开启 `config` feature 时使用 `BUILTIN_CARDS`，内容与表格里有效果的卡片一致，名称和描述已经是翻译后的文本
+*/
+
+#![cfg_attr(not(feature = "config"), allow(dead_code))]
+
+#[cfg(feature = "config")]
+use crate::config;
+use godot::prelude::*;
+use log::warn;
+
+/// What a card does to a run
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CardEffect {
+    /// Widen the Blue button's safe power range, 0.2 is +20%
+    BlueRange(f64),
+    /// Slow down the Pink button's time drift, 0.3 is 30% slower
+    PinkDrift(f64),
+    /// Seconds added to the Red button's press window
+    RedWindow(f64),
+    /// Failures the run survives, each one rolls the run back instead
+    ExtraLife(i32),
+    /// Rewind charges for the run
+    Rewind(i32),
+}
+
+impl CardEffect {
+    /// Effect of a `TbCardEffects` row, `None` for unknown effect names
+    pub fn parse(effect: &str, value: f64) -> Option<Self> {
+        match effect {
+            "blue_range" => Some(Self::BlueRange(value)),
+            "pink_drift" => Some(Self::PinkDrift(value)),
+            "red_window" => Some(Self::RedWindow(value)),
+            "extra_life" => Some(Self::ExtraLife(value as i32)),
+            "rewind" => Some(Self::Rewind(value as i32)),
+            _ => None,
+        }
+    }
+
+    /// The part property the effect changes, `None` for effects on the run itself
+    fn modifier(self) -> Option<Modifier> {
+        match self {
+            Self::BlueRange(amount) => Some(Modifier::new(
+                "BlueButton",
+                "range_scale",
+                Op::Scale(1.0 + amount),
+            )),
+            Self::PinkDrift(amount) => Some(Modifier::new(
+                "PinkButton",
+                "drift_delta",
+                Op::Scale(1.0 - amount),
+            )),
+            Self::RedWindow(seconds) => {
+                Some(Modifier::new("RedButton", "time_limit", Op::Add(seconds)))
+            }
+            Self::ExtraLife(_) | Self::Rewind(_) => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Card {
+    pub name: String,
+    /// Localization key of the name, translate before showing it
+    pub display_name: String,
+    /// Localization key of the description, translate before showing it
+    pub description: String,
+    /// Relative chance of being offered
+    pub weight: i32,
+    pub effects: Vec<CardEffect>,
+}
+
+/// Cards used without `config`: (name, display name, description, effect), kept in sync with the card tables
+#[cfg(not(feature = "config"))]
+const BUILTIN_CARDS: [(&str, &str, &str, CardEffect); 5] = [
+    (
+        "Capacitor",
+        "电容",
+        "蓝色按钮的安全电量范围扩大20%",
+        CardEffect::BlueRange(0.2),
+    ),
+    (
+        "Flywheel",
+        "飞轮",
+        "粉色按钮的时间流速变化减慢30%",
+        CardEffect::PinkDrift(0.3),
+    ),
+    (
+        "Spare_core",
+        "备用核心",
+        "失败时回到几秒前继续，一次",
+        CardEffect::ExtraLife(1),
+    ),
+    (
+        "Relay",
+        "继电器",
+        "红色按钮的按下时限增加0.5秒",
+        CardEffect::RedWindow(0.5),
+    ),
+    ("Hourglass", "沙漏", "获得一次倒带", CardEffect::Rewind(1)),
+];
+
+impl Card {
+    /// Every built-in card, in table order
+    #[cfg(not(feature = "config"))]
+    pub fn all() -> Vec<Card> {
+        BUILTIN_CARDS
+            .iter()
+            .map(|&(name, display_name, description, effect)| Card {
+                name: name.to_string(),
+                display_name: display_name.to_string(),
+                description: description.to_string(),
+                weight: 1,
+                effects: vec![effect],
+            })
+            .collect()
+    }
+
+    /// Every card with at least one effect, in table order
+    #[cfg(feature = "config")]
+    pub fn all() -> Vec<Card> {
+        let Some(tables) = config::tables() else {
+            return Vec::new();
+        };
+        tables
+            .TbCardsInfo
+            .data_list
+            .iter()
+            .filter_map(|info| {
+                let effects = tables
+                    .TbCardEffects
+                    .data_list
+                    .iter()
+                    .filter(|row| row.card_name == info.base_cardName)
+                    .filter_map(|row| {
+                        let effect = CardEffect::parse(&row.effect, row.value as f64);
+                        if effect.is_none() {
+                            warn!("card {} has unknown effect {}", row.card_name, row.effect);
+                        }
+                        effect
+                    })
+                    .collect::<Vec<_>>();
+                if effects.is_empty() {
+                    return None;
+                }
+                Some(Card {
+                    name: info.base_cardName.clone(),
+                    display_name: info.base_displayName.clone(),
+                    description: info.base_description.clone(),
+                    weight: info.base_cardWeight,
+                    effects,
+                })
+            })
+            .collect()
+    }
+
+    pub fn get(name: &str) -> Option<Card> {
+        Self::all().into_iter().find(|card| card.name == name)
+    }
+
+    pub fn extra_lives(cards: &[Card]) -> i32 {
+        Self::effects(cards)
+            .map(|effect| match effect {
+                CardEffect::ExtraLife(count) => count,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    pub fn rewinds(cards: &[Card]) -> i32 {
+        Self::effects(cards)
+            .map(|effect| match effect {
+                CardEffect::Rewind(count) => count,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    fn effects(cards: &[Card]) -> impl Iterator<Item = CardEffect> + '_ {
+        cards.iter().flat_map(|card| card.effects.iter().copied())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Add(f64),
+    Scale(f64),
+}
+
+/// A change to one property of every part of a class
+#[derive(Clone, Copy, Debug)]
+struct Modifier {
+    target: &'static str,
+    property: &'static str,
+    op: Op,
+}
+
+impl Modifier {
+    const fn new(target: &'static str, property: &'static str, op: Op) -> Self {
+        Self {
+            target,
+            property,
+            op,
+        }
+    }
+}
+
+/// Part properties changed by the cards of a run, with their values from before the run
+#[derive(Default)]
+pub struct Modifiers {
+    originals: Vec<(Gd<Node>, &'static str, Variant)>,
+}
+
+impl Modifiers {
+    /// Apply the effects of `cards` on top of the current property values of `parts`
+    pub fn apply(&mut self, cards: &[Card], parts: &[Gd<Node>]) {
+        let modifiers = Card::effects(cards).filter_map(CardEffect::modifier);
+        for modifier in modifiers {
+            for part in parts.iter().filter(|part| part.is_class(modifier.target)) {
+                self.apply_one(part.clone(), modifier);
+            }
+        }
+    }
+
+    fn apply_one(&mut self, mut part: Gd<Node>, modifier: Modifier) {
+        let current = part.get(modifier.property);
+        let Ok(value) = current.try_to::<f64>() else {
+            warn!("{} has no property {}", modifier.target, modifier.property);
+            return;
+        };
+        // 同一属性被多张卡片修改时只记录最初的值
+        let recorded = self
+            .originals
+            .iter()
+            .any(|(node, property, _)| *node == part && *property == modifier.property);
+        if !recorded {
+            self.originals
+                .push((part.clone(), modifier.property, current));
+        }
+
+        let value = match modifier.op {
+            Op::Add(amount) => value + amount,
+            Op::Scale(factor) => value * factor,
+        };
+        part.set(modifier.property, &value.to_variant());
+    }
+
+    /// Whether `apply` changed any property that `revert` has not put back yet
+    pub fn is_empty(&self) -> bool {
+        self.originals.is_empty()
+    }
+
+    /// Put back the values from before `apply`, parts freed since are skipped
+    pub fn revert(&mut self) {
+        for (mut part, property, value) in self.originals.drain(..).rev() {
+            if part.is_instance_valid() {
+                part.set(property, &value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(name: &str, effects: Vec<CardEffect>) -> Card {
+        Card {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            description: format!("{name}_desc"),
+            weight: 1,
+            effects,
+        }
+    }
+
+    #[test]
+    fn parses_known_effects() {
+        assert_eq!(
+            CardEffect::parse("blue_range", 0.2),
+            Some(CardEffect::BlueRange(0.2))
+        );
+        assert_eq!(
+            CardEffect::parse("pink_drift", 0.3),
+            Some(CardEffect::PinkDrift(0.3))
+        );
+        assert_eq!(
+            CardEffect::parse("red_window", 0.5),
+            Some(CardEffect::RedWindow(0.5))
+        );
+        assert_eq!(
+            CardEffect::parse("extra_life", 1.0),
+            Some(CardEffect::ExtraLife(1))
+        );
+        assert_eq!(
+            CardEffect::parse("rewind", 2.0),
+            Some(CardEffect::Rewind(2))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_effects() {
+        assert_eq!(CardEffect::parse("", 1.0), None);
+        assert_eq!(CardEffect::parse("Rewind", 1.0), None);
+        assert_eq!(CardEffect::parse("gold", 1.0), None);
+    }
+
+    #[test]
+    fn counts_lives_and_rewinds_over_all_cards() {
+        let cards = [
+            card("Spare_core", vec![CardEffect::ExtraLife(1)]),
+            card("Hourglass", vec![CardEffect::Rewind(1)]),
+            card(
+                "Relay",
+                vec![
+                    CardEffect::RedWindow(0.5),
+                    CardEffect::ExtraLife(2),
+                    CardEffect::Rewind(1),
+                ],
+            ),
+            card("Capacitor", vec![CardEffect::BlueRange(0.2)]),
+        ];
+        assert_eq!(Card::extra_lives(&cards), 3);
+        assert_eq!(Card::rewinds(&cards), 2);
+    }
+
+    #[cfg(not(feature = "config"))]
+    #[test]
+    fn builtin_cards_have_unique_names() {
+        let cards = Card::all();
+        assert_eq!(cards.len(), BUILTIN_CARDS.len());
+        for card in &cards {
+            assert_eq!(
+                cards.iter().filter(|other| other.name == card.name).count(),
+                1
+            );
+            assert_eq!(
+                Card::get(&card.name).map(|found| found.effects),
+                Some(card.effects.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn no_cards_give_nothing() {
+        assert_eq!(Card::extra_lives(&[]), 0);
+        assert_eq!(Card::rewinds(&[]), 0);
+        let cards = [card("Flywheel", vec![CardEffect::PinkDrift(0.3)])];
+        assert_eq!(Card::extra_lives(&cards), 0);
+        assert_eq!(Card::rewinds(&cards), 0);
+    }
+}
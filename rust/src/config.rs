@@ -0,0 +1,201 @@
+/*!
+配置表：读取 Luban 导出到 `res://data/bytes` 的数据（见 data/gen.bat），代码生成在 `rust/gen`
+`part.TbPartParams` 的每一行是 (节点类名, 属性名, 数值)，在放置零件和重新读取配置时写入对应的节点
+调试版本会监视数据目录，重新导出后不用重启场景就能看到新数值
+生成代码用的模板在 Luban 自带模板里插入 data/Templates/godot 的片段，每个 bean 和枚举都实现了 ToGodot/FromGodot，可以直接传给 GDScript 或作为导出属性
+`vector2/3/4` 对应 Godot 的 Vector2/3/4，其他 bean 对应 Dictionary，枚举对应整数
+仓库里只有生成 crate 的占位，生成后开启 `config` feature 才会读取配置表，否则零件使用场景里导出的默认值
+*/
+
+#[cfg(feature = "config")]
+use crate::game_signals::GameSignals;
+#[cfg(feature = "config")]
+use cfg::{LubanError, Tables};
+#[cfg(feature = "config")]
+use godot::classes::node::ProcessMode;
+#[cfg(feature = "config")]
+use godot::classes::{DirAccess, FileAccess, Time};
+use godot::prelude::*;
+use log::{info, warn};
+#[cfg(feature = "config")]
+use luban_lib::ByteBuf;
+#[cfg(feature = "config")]
+use std::collections::HashMap;
+#[cfg(feature = "config")]
+use std::sync::{Arc, RwLock};
+
+#[cfg(feature = "config")]
+pub const DATA_DIR: &str = "res://data/bytes";
+
+#[cfg(feature = "config")]
+static TABLES: RwLock<Option<Arc<Tables>>> = RwLock::new(None);
+
+/// Read every table, the tables loaded before are kept if the new data can't be read
+#[cfg(feature = "config")]
+pub fn load() -> Result<(), String> {
+    let tables = Tables::new(|name| {
+        let path = format!("{DATA_DIR}/{name}.bytes");
+        let bytes = FileAccess::get_file_as_bytes(&path);
+        if bytes.is_empty() {
+            return Err(LubanError::Loader(format!("{path} can not be read")));
+        }
+        Ok(ByteBuf::new(bytes.to_vec()))
+    })
+    .map_err(|error| error.to_string())?;
+    *TABLES.write().unwrap() = Some(Arc::new(tables));
+    Ok(())
+}
+
+#[cfg(not(feature = "config"))]
+pub fn load() -> Result<(), String> {
+    info!("built without the config feature, config tables are not loaded");
+    Ok(())
+}
+
+/// Tables loaded last, `None` if no data could be read yet
+#[cfg(feature = "config")]
+pub fn tables() -> Option<Arc<Tables>> {
+    TABLES.read().unwrap().clone()
+}
+
+/// Read the tables again and emit `config_reloaded` so running nodes pick up the new values
+#[cfg(feature = "config")]
+pub fn reload() -> Result<(), String> {
+    load()?;
+    info!("config tables reloaded");
+    GameSignals::singleton().signals().config_reloaded().emit();
+    Ok(())
+}
+
+#[cfg(all(not(feature = "config"), feature = "dev-tools"))]
+pub fn reload() -> Result<(), String> {
+    Err("built without the config feature".to_string())
+}
+
+#[cfg(not(feature = "config"))]
+pub fn params(_target: &str) -> Vec<(String, f64)> {
+    Vec::new()
+}
+
+/// Parameters of a node class as (property, value)
+#[cfg(feature = "config")]
+pub fn params(target: &str) -> Vec<(String, f64)> {
+    let Some(tables) = tables() else {
+        return Vec::new();
+    };
+    tables
+        .TbPartParams
+        .data_list
+        .iter()
+        .filter(|row| row.target == target)
+        .map(|row| (row.property.clone(), row.value as f64))
+        .collect()
+}
+
+/// Text of a localization key, looked up in the `#translate` table since the exported `text`
+/// fields hold keys; the key itself when it has no translation
+#[cfg(feature = "config")]
+pub fn text(key: &str) -> String {
+    tables()
+        .and_then(|tables| {
+            tables
+                .TbTranslate
+                .data_list
+                .iter()
+                .find(|row| row.key == key)
+                .map(|row| row.cn.clone())
+        })
+        .unwrap_or_else(|| key.to_string())
+}
+
+#[cfg(not(feature = "config"))]
+pub fn text(key: &str) -> String {
+    key.to_string()
+}
+
+/// Set the parameters of the node's class on the node, through the property setters
+pub fn apply_params(node: &mut Gd<Node>) {
+    let class = node.get_class().to_string();
+    for (property, value) in params(&class) {
+        if node.get(property.as_str()).is_nil() {
+            warn!("{} has no property {}", class, property);
+            continue;
+        }
+        node.set(property.as_str(), &value.to_variant());
+    }
+}
+
+/// Start watching the data directory, debug builds only
+#[cfg(feature = "config")]
+pub fn watch(parent: &mut Gd<Node>) {
+    if cfg!(debug_assertions) {
+        let watcher = ConfigWatcher::new_alloc();
+        // 父节点可能还在 ready 中，延迟添加子节点
+        parent.call_deferred("add_child", &[watcher.to_variant()]);
+    }
+}
+
+#[cfg(not(feature = "config"))]
+pub fn watch(_parent: &mut Gd<Node>) {}
+
+/// ConfigWatcher reloads the tables after gen.bat wrote new data
+#[cfg(feature = "config")]
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct ConfigWatcher {
+    base: Base<Node>,
+    /// Milliseconds between two scans of the data directory
+    #[init(val = 1000)]
+    interval: u64,
+    last_scan: u64,
+    modified: HashMap<GString, u64>,
+    /// Files changed in the last scan, reload once they stop changing
+    pending: bool,
+}
+
+#[cfg(feature = "config")]
+#[godot_api]
+impl INode for ConfigWatcher {
+    fn ready(&mut self) {
+        // 暂停时也要能刷新数值
+        self.base_mut().set_process_mode(ProcessMode::ALWAYS);
+        self.modified = Self::scan();
+    }
+
+    fn process(&mut self, _delta: f64) {
+        // 粉色零件会改变时间流速，用真实时间计时
+        let now = Time::singleton().get_ticks_msec();
+        if now - self.last_scan < self.interval {
+            return;
+        }
+        self.last_scan = now;
+
+        let modified = Self::scan();
+        if modified != self.modified {
+            // gen.bat 逐个写文件，等写完再读取
+            self.modified = modified;
+            self.pending = true;
+            return;
+        }
+        if self.pending {
+            self.pending = false;
+            if let Err(error) = reload() {
+                warn!("Failed to reload config tables: {}", error);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl ConfigWatcher {
+    fn scan() -> HashMap<GString, u64> {
+        DirAccess::get_files_at(DATA_DIR)
+            .as_slice()
+            .iter()
+            .map(|file| {
+                let path = format!("{DATA_DIR}/{file}");
+                (file.clone(), FileAccess::get_modified_time(&path))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,436 @@
+use crate::cards::Card;
+use crate::config;
+use crate::game_controller::Game;
+use crate::game_signals::GameSignals;
+use crate::logging;
+use crate::parts::{BlueButton, PinkButton, RedButton, YellowButton};
+use crate::settings::Settings;
+use crate::snapshot::RunSnapshot;
+use godot::classes::control::{LayoutPreset, SizeFlags};
+use godot::classes::node::ProcessMode;
+use godot::classes::{
+    Control, Engine, IControl, InputEvent, InputEventKey, Label, LineEdit, PanelContainer,
+    VBoxContainer,
+};
+use godot::global::Key;
+use godot::prelude::*;
+use log::{LevelFilter, info};
+
+const HELP: &str = "win | fail <原因> | timescale <倍数> | freeze <零件> | spawn <零件id> \
+| set <零件>.<字段> <值> | seed <n> | reload_config | log <模块|all> <级别> | log file on/off \
+| save [名字] | load [名字] | card [卡片名] | help
+零件可以写 red/yellow/blue/pink 或 id，F5 快速存档，F9 快速读档";
+/// Snapshot name used by F5/F9 and by save/load without a name
+const QUICK_SNAPSHOT: &str = "quick";
+/// Lines of output kept on screen
+const MAX_LINES: usize = 12;
+
+enum Command {
+    Help,
+    Win,
+    Fail(String),
+    TimeScale(f64),
+    Freeze(String),
+    Spawn(i32),
+    Set {
+        part: String,
+        field: String,
+        value: String,
+    },
+    Seed(i64),
+    ReloadConfig,
+    /// `None` target sets the default level
+    Log(Option<String>, LevelFilter),
+    LogFile(bool),
+    SaveSnapshot(String),
+    LoadSnapshot(String),
+    /// `None` lists the cards
+    Card(Option<String>),
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or("空命令")?;
+        let rest = words.collect::<Vec<_>>();
+        let arg = |index: usize| rest.get(index).copied().ok_or(format!("{name} 缺少参数"));
+
+        match name {
+            "help" => Ok(Command::Help),
+            "win" => Ok(Command::Win),
+            "fail" => Ok(Command::Fail(if rest.is_empty() {
+                "控制台".to_string()
+            } else {
+                rest.join(" ")
+            })),
+            "timescale" => arg(0)?
+                .parse()
+                .map(Command::TimeScale)
+                .map_err(|_| "timescale 需要一个数字".to_string()),
+            "freeze" => Ok(Command::Freeze(arg(0)?.to_string())),
+            "spawn" => arg(0)?
+                .parse()
+                .map(Command::Spawn)
+                .map_err(|_| "spawn 需要零件id".to_string()),
+            "set" => {
+                let (part, field) = arg(0)?
+                    .split_once('.')
+                    .ok_or("set 的格式是 <零件>.<字段> <值>")?;
+                Ok(Command::Set {
+                    part: part.to_string(),
+                    field: field.to_string(),
+                    value: arg(1)?.to_string(),
+                })
+            }
+            "seed" => arg(0)?
+                .parse()
+                .map(Command::Seed)
+                .map_err(|_| "seed 需要一个整数".to_string()),
+            "reload_config" => Ok(Command::ReloadConfig),
+            "log" => match (arg(0)?, arg(1)?) {
+                ("file", "on") => Ok(Command::LogFile(true)),
+                ("file", "off") => Ok(Command::LogFile(false)),
+                (target, level) => {
+                    let level = Settings::parse_level(level)
+                        .ok_or("级别可以是 off/error/warn/info/debug/trace")?;
+                    let target = (target != "all").then(|| target.to_string());
+                    Ok(Command::Log(target, level))
+                }
+            },
+            "save" => Ok(Command::SaveSnapshot(
+                arg(0).unwrap_or(QUICK_SNAPSHOT).to_string(),
+            )),
+            "load" => Ok(Command::LoadSnapshot(
+                arg(0).unwrap_or(QUICK_SNAPSHOT).to_string(),
+            )),
+            "card" => Ok(Command::Card(arg(0).ok().map(str::to_string))),
+            _ => Err(format!("未知命令 {name}，输入 help 查看全部命令")),
+        }
+    }
+}
+
+/// DevConsole runs cheat commands typed by the developer, toggled with the ` key
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct DevConsole {
+    base: Base<Control>,
+    pub game: Option<Gd<Game>>,
+    #[init(val = LineEdit::new_alloc())]
+    input: Gd<LineEdit>,
+    #[init(val = Label::new_alloc())]
+    output: Gd<Label>,
+    lines: Vec<String>,
+}
+
+#[godot_api]
+impl IControl for DevConsole {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+        // 暂停时也能使用
+        self.base_mut().set_process_mode(ProcessMode::ALWAYS);
+        self.base_mut().set_anchors_preset(LayoutPreset::TOP_WIDE);
+        self.base_mut()
+            .set_custom_minimum_size(Vector2::new(0.0, 260.0));
+
+        let mut panel = PanelContainer::new_alloc();
+        panel.set_anchors_preset(LayoutPreset::FULL_RECT);
+        let mut vbox = VBoxContainer::new_alloc();
+        self.output.set_v_size_flags(SizeFlags::EXPAND_FILL);
+        self.output
+            .set_vertical_alignment(godot::global::VerticalAlignment::BOTTOM);
+        self.input.set_placeholder("输入命令，help 查看全部命令");
+        self.input
+            .signals()
+            .text_submitted()
+            .connect_other(self, Self::on_text_submitted);
+        vbox.add_child(&self.output);
+        vbox.add_child(&self.input);
+        panel.add_child(&vbox);
+        self.base_mut().add_child(&panel);
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        let Ok(key_event) = event.try_cast::<InputEventKey>() else {
+            return;
+        };
+        if !key_event.is_pressed() || key_event.is_echo() {
+            return;
+        }
+        let command = match key_event.get_physical_keycode() {
+            Key::QUOTELEFT => None,
+            Key::F5 => Some(Command::SaveSnapshot(QUICK_SNAPSHOT.to_string())),
+            Key::F9 => Some(Command::LoadSnapshot(QUICK_SNAPSHOT.to_string())),
+            _ => return,
+        };
+        if let Some(command) = command {
+            match self.execute(command) {
+                Ok(message) => self.print(message),
+                Err(message) => self.print(format!("错误：{message}")),
+            }
+            self.base().get_viewport().unwrap().set_input_as_handled();
+            return;
+        }
+
+        let visible = !self.base().is_visible();
+        self.base_mut().set_visible(visible);
+        if visible {
+            self.input.clear();
+            self.input.grab_focus();
+        }
+        self.base().get_viewport().unwrap().set_input_as_handled();
+    }
+}
+
+#[godot_api]
+impl DevConsole {
+    fn on_text_submitted(&mut self, text: GString) {
+        self.input.clear();
+        let line = text.to_string();
+        if line.trim().is_empty() {
+            return;
+        }
+
+        self.print(format!("> {line}"));
+        let result = Command::parse(&line).and_then(|command| self.execute(command));
+        match result {
+            Ok(message) => self.print(message),
+            Err(message) => self.print(format!("错误：{message}")),
+        }
+    }
+
+    fn execute(&mut self, command: Command) -> Result<String, String> {
+        let mut game = self.game.clone().ok_or("没有找到 Game")?;
+
+        match command {
+            Command::Help => Ok(HELP.to_string()),
+            Command::Win => {
+                if !game.bind().is_running() {
+                    return Err("当前没有进行中的游戏".to_string());
+                }
+                GameSignals::singleton().signals().game_victory().emit();
+                Ok("胜利".to_string())
+            }
+            Command::Fail(reason) => {
+                if !game.bind().is_running() {
+                    return Err("当前没有进行中的游戏".to_string());
+                }
+                GameSignals::singleton()
+                    .bind_mut()
+                    .emit_game_failure(&reason);
+                Ok(format!("失败：{reason}"))
+            }
+            Command::TimeScale(scale) => {
+                if scale <= 0.0 {
+                    return Err("时间流速必须大于0".to_string());
+                }
+                Engine::singleton().set_time_scale(scale);
+                Ok(format!("时间流速 {scale}"))
+            }
+            Command::Freeze(name) => {
+                let mut part = Self::find_part(&game, &name)?;
+                // 冻结零件和它的计时器
+                let frozen = part.get_process_mode() == ProcessMode::DISABLED;
+                let mode = if frozen {
+                    ProcessMode::INHERIT
+                } else {
+                    ProcessMode::DISABLED
+                };
+                part.set_process_mode(mode);
+                Ok(format!(
+                    "{} {}",
+                    part.get_name(),
+                    if frozen { "已解冻" } else { "已冻结" }
+                ))
+            }
+            Command::Spawn(part_id) => {
+                game.bind_mut().spawn_part(part_id)?;
+                Ok(format!("已放置零件 {part_id}，下一局开始时生效"))
+            }
+            Command::Set { part, field, value } => {
+                let mut part = Self::find_part(&game, &part)?;
+                let current = part.get(field.as_str());
+                let value = match current.get_type() {
+                    VariantType::NIL => {
+                        return Err(format!("{} 没有字段 {field}", part.get_name()));
+                    }
+                    VariantType::BOOL => value
+                        .parse::<bool>()
+                        .map(|value| value.to_variant())
+                        .map_err(|_| ()),
+                    VariantType::INT => value
+                        .parse::<i64>()
+                        .map(|value| value.to_variant())
+                        .map_err(|_| ()),
+                    _ => value
+                        .parse::<f64>()
+                        .map(|value| value.to_variant())
+                        .map_err(|_| ()),
+                }
+                .map_err(|_| format!("{value} 不是 {field} 能接受的值"))?;
+                part.set(field.as_str(), &value);
+                Ok(format!(
+                    "{}.{field} = {}",
+                    part.get_name(),
+                    part.get(field.as_str())
+                ))
+            }
+            Command::Seed(seed) => {
+                game.set("seed", &seed.to_variant());
+                Ok(format!("下一局的种子 {seed}"))
+            }
+            Command::ReloadConfig => {
+                let mut settings = Settings::singleton();
+                settings.bind_mut().load();
+                settings.bind_mut().apply();
+                config::reload()?;
+                Ok("已重新读取设置和配置表".to_string())
+            }
+            Command::Log(None, level) => {
+                logging::set_default_level(level);
+                Ok(format!("默认日志级别 {level}"))
+            }
+            Command::Log(Some(target), level) => {
+                logging::set_target_level(&target, level);
+                Ok(format!("{target} 日志级别 {level}"))
+            }
+            Command::LogFile(enabled) => {
+                logging::set_file_enabled(enabled);
+                Ok(format!(
+                    "日志文件 {}",
+                    if enabled { "已开启" } else { "已关闭" }
+                ))
+            }
+            Command::SaveSnapshot(name) => {
+                let snapshot = game.bind().snapshot().ok_or("当前没有进行中的游戏")?;
+                let path = snapshot.save(&name).ok_or("快照保存失败")?;
+                Ok(format!("快照已保存到 {path}"))
+            }
+            Command::LoadSnapshot(name) => {
+                let snapshot = RunSnapshot::load(&name).ok_or(format!("快照 {name} 读取失败"))?;
+                game.bind_mut().restore_snapshot(&snapshot);
+                Ok(format!("已读取快照 {name}"))
+            }
+            Command::Card(None) => {
+                let held = game
+                    .bind()
+                    .cards()
+                    .iter()
+                    .map(|card| card.name.to_string())
+                    .collect::<Vec<_>>();
+                let all = Card::all()
+                    .iter()
+                    .map(|card| card.name.to_string())
+                    .collect::<Vec<_>>();
+                Ok(format!(
+                    "手上的卡片：{}\n全部卡片：{}",
+                    held.join(" "),
+                    all.join(" ")
+                ))
+            }
+            Command::Card(Some(name)) => {
+                if !game.bind_mut().add_card(name.as_str().into()) {
+                    return Err(format!("未知卡片 {name}"));
+                }
+                Ok(format!("已获得卡片 {name}，下一局开始时生效"))
+            }
+        }
+    }
+
+    /// Find a part under `Parts` by color or part id
+    fn find_part(game: &Gd<Game>, name: &str) -> Result<Gd<Node>, String> {
+        let is_part: fn(&Gd<Node>) -> bool = match name {
+            "red" | "1001" => |part| part.clone().try_cast::<RedButton>().is_ok(),
+            "yellow" | "1002" => |part| part.clone().try_cast::<YellowButton>().is_ok(),
+            "blue" | "1003" => |part| part.clone().try_cast::<BlueButton>().is_ok(),
+            "pink" | "1005" => |part| part.clone().try_cast::<PinkButton>().is_ok(),
+            _ => return Err(format!("未知零件 {name}")),
+        };
+        game.get_node_as::<Node>("Parts")
+            .get_children()
+            .iter_shared()
+            .find(is_part)
+            .ok_or(format!("当前关卡没有零件 {name}"))
+    }
+
+    fn print(&mut self, line: String) {
+        info!("{}", line);
+        self.lines.extend(line.lines().map(str::to_string));
+        let overflow = self.lines.len().saturating_sub(MAX_LINES);
+        self.lines.drain(..overflow);
+        self.output.set_text(&self.lines.join("\n"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_with_arguments() {
+        assert!(
+            matches!(Command::parse("timescale 0.5"), Ok(Command::TimeScale(scale)) if scale == 0.5)
+        );
+        assert!(matches!(
+            Command::parse("spawn 1003"),
+            Ok(Command::Spawn(1003))
+        ));
+        assert!(matches!(
+            Command::parse("  seed   42 "),
+            Ok(Command::Seed(42))
+        ));
+        assert!(matches!(Command::parse("freeze red"), Ok(Command::Freeze(part)) if part == "red"));
+        assert!(matches!(
+            Command::parse("set blue.score_rate 20"),
+            Ok(Command::Set { part, field, value }) if part == "blue" && field == "score_rate" && value == "20"
+        ));
+    }
+
+    #[test]
+    fn fail_joins_the_reason_and_has_a_default() {
+        assert!(
+            matches!(Command::parse("fail 红色 超时"), Ok(Command::Fail(reason)) if reason == "红色 超时")
+        );
+        assert!(matches!(Command::parse("fail"), Ok(Command::Fail(reason)) if reason == "控制台"));
+    }
+
+    #[test]
+    fn optional_arguments_fall_back() {
+        assert!(
+            matches!(Command::parse("save"), Ok(Command::SaveSnapshot(name)) if name == QUICK_SNAPSHOT)
+        );
+        assert!(
+            matches!(Command::parse("load boss"), Ok(Command::LoadSnapshot(name)) if name == "boss")
+        );
+        assert!(matches!(Command::parse("card"), Ok(Command::Card(None))));
+        assert!(
+            matches!(Command::parse("card Relay"), Ok(Command::Card(Some(name))) if name == "Relay")
+        );
+    }
+
+    #[test]
+    fn parses_log_commands() {
+        assert!(matches!(
+            Command::parse("log file on"),
+            Ok(Command::LogFile(true))
+        ));
+        assert!(matches!(
+            Command::parse("log all debug"),
+            Ok(Command::Log(None, LevelFilter::Debug))
+        ));
+        assert!(matches!(
+            Command::parse("log audio WARN"),
+            Ok(Command::Log(Some(target), LevelFilter::Warn)) if target == "audio"
+        ));
+        assert!(Command::parse("log audio loud").is_err());
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(Command::parse("").is_err());
+        assert!(Command::parse("dance").is_err());
+        assert!(Command::parse("timescale").is_err());
+        assert!(Command::parse("timescale fast").is_err());
+        assert!(Command::parse("spawn red").is_err());
+        assert!(Command::parse("set blue 20").is_err());
+    }
+}
@@ -0,0 +1,34 @@
+/*!
+开发者工具，只在启用 `dev-tools` feature 时编译
+控制台和调试面板放在最上层的 CanvasLayer 里，不进入正式版的场景文件
+*/
+
+mod console;
+mod overlay;
+
+use crate::game_controller::Game;
+use console::DevConsole;
+use godot::classes::CanvasLayer;
+use godot::prelude::*;
+use overlay::DebugOverlay;
+
+/// Draw order of the dev tools layer, above every game screen
+const LAYER: i32 = 100;
+
+/// Add the dev tools to the main scene
+pub fn install(game: &mut Gd<Game>) {
+    let mut layer = CanvasLayer::new_alloc();
+    layer.set_name("DevTools");
+    layer.set_layer(LAYER);
+
+    let mut console = DevConsole::new_alloc();
+    console.bind_mut().game = Some(game.clone());
+    layer.add_child(&console);
+
+    let mut overlay = DebugOverlay::new_alloc();
+    overlay.bind_mut().game = Some(game.clone());
+    layer.add_child(&overlay);
+
+    // 主场景还在 ready 中，延迟添加子节点
+    game.call_deferred("add_child", &[layer.to_variant()]);
+}
@@ -0,0 +1,66 @@
+use crate::game_controller::Game;
+use godot::classes::control::LayoutPreset;
+use godot::classes::node::ProcessMode;
+use godot::classes::{ILabel, InputEvent, InputEventKey, Label};
+use godot::global::Key;
+use godot::prelude::*;
+
+/// DebugOverlay lists the game timer and the internals of every part each frame, toggled with F3
+#[derive(GodotClass)]
+#[class(init, base=Label)]
+pub struct DebugOverlay {
+    base: Base<Label>,
+    pub game: Option<Gd<Game>>,
+}
+
+#[godot_api]
+impl ILabel for DebugOverlay {
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+        self.base_mut().set_process_mode(ProcessMode::ALWAYS);
+        self.base_mut()
+            .set_anchors_preset(LayoutPreset::BOTTOM_LEFT);
+        self.base_mut().set_position(Vector2::new(8.0, 300.0));
+        self.base_mut()
+            .set_mouse_filter(godot::classes::control::MouseFilter::IGNORE);
+    }
+
+    fn process(&mut self, _delta: f64) {
+        if !self.base().is_visible() {
+            return;
+        }
+        let Some(game) = &self.game else {
+            return;
+        };
+
+        let text = {
+            let game = game.bind();
+            let mut text = match game.time_left() {
+                Some(time_left) => format!("Game {:?} time_left={:.2}s\n", game.state(), time_left),
+                None => format!("Game {:?} no run\n", game.state()),
+            };
+            for part in game.parts() {
+                let name = part.get_name();
+                text += &format!("{}: {}\n", name, part.dyn_bind().debug_state());
+            }
+            text
+        };
+        self.base_mut().set_text(&text);
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        let Ok(key_event) = event.try_cast::<InputEventKey>() else {
+            return;
+        };
+        if !key_event.is_pressed()
+            || key_event.is_echo()
+            || key_event.get_physical_keycode() != Key::F3
+        {
+            return;
+        }
+
+        let visible = !self.base().is_visible();
+        self.base_mut().set_visible(visible);
+        self.base().get_viewport().unwrap().set_input_as_handled();
+    }
+}
@@ -1,7 +1,72 @@
+use crate::cards::{Card, Modifiers};
+use crate::config;
 use crate::game_signals::GameSignals;
-use godot::classes::Timer;
+use crate::hot_reload;
+use crate::levels::{DRAFT_PARTS, Level};
+use crate::parts::{Part, part_scene, resume_timer, state_value};
+use crate::records::{RunHistory, RunOutcome, RunRecord, RunSummary};
+use crate::replay::Replay;
+use crate::settings::Settings;
+use crate::site;
+use crate::snapshot::{PartSnapshot, RunSnapshot};
+use crate::tutorial;
+use godot::classes::{Engine, InputEvent, Time, Timer};
+use godot::global::{randi, randomize};
+use godot::meta::{ByValue, ParamType};
 use godot::prelude::*;
-use crate::godot_print_err;
+use log::{error, info, warn};
+use std::collections::VecDeque;
+
+#[derive(GodotConvert, Var, Export, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[godot(via = GString)]
+pub enum GameMode {
+    #[default]
+    Normal,
+    /// Guided introduction of one part at a time, runs are not recorded
+    Tutorial,
+    /// Sandbox with a single part whose parameters can be tuned, runs are not recorded
+    Practice,
+}
+
+#[derive(GodotConvert, Var, Export, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[godot(via = GString)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// Multiplier applied to `Game.time_limit`, a shorter round is easier to survive
+    pub fn time_limit_scale(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 2.0,
+        }
+    }
+}
+
+/// Where the player is in the menu -> level select -> play -> result flow
+#[derive(GodotConvert, Var, Export, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[godot(via = GString)]
+pub enum GameState {
+    #[default]
+    Menu,
+    LevelSelect,
+    Countdown,
+    Playing,
+    Paused,
+    Result,
+}
+
+// 让状态能直接作为类型化信号的参数按值传递
+impl ParamType for GameState {
+    type ArgPassing = ByValue;
+}
 
 #[derive(GodotClass)]
 #[class(init, base = Node3D)]
@@ -10,12 +75,105 @@ pub struct Game {
 
     #[export]
     time_limit: f64,
+    #[export]
+    mode: GameMode,
+    #[export]
+    #[init(val = 1)]
+    level: i32,
+    /// Taken from `Settings` when a run starts
+    difficulty: Difficulty,
+    /// Seed for the run RNG, 0 picks a new one every run
+    #[export]
+    seed: i64,
+    /// Seconds counted down before a run starts, 0 starts right away
+    #[export]
+    #[init(val = 3)]
+    countdown: i32,
+    #[init(val = Timer::new_alloc())]
+    countdown_timer: Gd<Timer>,
+    countdown_left: i32,
     timer: Option<Gd<Timer>>,
+    /// Sum of all part scores for the last finished run
+    score: f64,
+    run_seed: i64,
+    outcome: RunOutcome,
+    failure_reason: GString,
+    state: GameState,
+    /// State to go back to when the pause menu closes, the run may still be counting down
+    paused_state: GameState,
+    /// Inputs of the run in progress
+    replay: Replay,
+    /// Stats of the last finished run, kept for the result screens
+    last_run: Option<RunSummary>,
+    /// Seconds a rewind rolls the run back
+    #[export]
+    #[init(val = 3.0)]
+    rewind_seconds: f64,
+    /// Rewinds left in the run in progress
+    rewind_charges: i32,
+    /// Snapshots of the last `rewind_seconds` of the run, oldest first
+    rewind_buffer: VecDeque<RunSnapshot>,
+    /// Cards held by the player, their effects apply to every normal run
+    cards: Vec<Card>,
+    /// Part properties changed by the cards of the run in progress
+    modifiers: Modifiers,
+    /// Rounds cleared through the card draft since the level was loaded
+    round: i32,
+    /// Cards to pick from after a won round, empty when there is no draft
+    draft_offer: Vec<Card>,
 }
 
 #[godot_api]
 impl INode3D for Game {
     fn ready(&mut self) {
+        self.countdown_timer.set_wait_time(1.0);
+        self.countdown_timer.set_one_shot(false);
+        self.countdown_timer
+            .signals()
+            .timeout()
+            .connect_other(self, Self::on_countdown_timeout);
+        let timer = self.countdown_timer.clone();
+        self.base_mut().add_child(&timer);
+
+        GameSignals::singleton()
+            .signals()
+            .menu_requested()
+            .connect_other(self, |this| this.set_state(GameState::Menu));
+        GameSignals::singleton()
+            .signals()
+            .level_select_requested()
+            .connect_other(self, |this| this.set_state(GameState::LevelSelect));
+        GameSignals::singleton()
+            .signals()
+            .level_selected()
+            .connect_other(self, Self::select_level);
+        GameSignals::singleton()
+            .signals()
+            .tutorial_requested()
+            .connect_other(self, Self::select_tutorial_step);
+        GameSignals::singleton()
+            .signals()
+            .practice_requested()
+            .connect_other(self, Self::select_practice_part);
+        GameSignals::singleton()
+            .signals()
+            .next_level_requested()
+            .connect_other(self, |this| this.select_level(this.level + 1));
+        GameSignals::singleton()
+            .signals()
+            .game_requested()
+            .connect_other(self, Self::request_game);
+        GameSignals::singleton()
+            .signals()
+            .game_paused()
+            .connect_other(self, |this| {
+                this.paused_state = this.state;
+                this.set_state(GameState::Paused);
+            });
+        GameSignals::singleton()
+            .signals()
+            .game_resumed()
+            .connect_other(self, |this| this.set_state(this.paused_state));
         GameSignals::singleton()
             .signals()
             .game_started()
@@ -24,6 +182,35 @@ impl INode3D for Game {
             .signals()
             .game_stopped()
             .connect_other(self, Self::stop);
+        GameSignals::singleton()
+            .signals()
+            .config_reloaded()
+            .connect_other(self, Self::apply_config);
+        GameSignals::singleton()
+            .signals()
+            .life_lost()
+            .connect_other(self, Self::on_life_lost);
+        GameSignals::singleton()
+            .signals()
+            .card_picked()
+            .connect_other(self, Self::on_card_picked);
+
+        // 先记录结果，再停止游戏
+        GameSignals::singleton()
+            .signals()
+            .game_victory()
+            .connect_other(self, |this| {
+                this.outcome = RunOutcome::Victory;
+                this.set_state(GameState::Result);
+            });
+        GameSignals::singleton()
+            .signals()
+            .game_failure()
+            .connect_other(self, |this, reason| {
+                this.outcome = RunOutcome::Failure;
+                this.failure_reason = reason;
+                this.set_state(GameState::Result);
+            });
 
         GameSignals::singleton()
             .signals()
@@ -35,16 +222,291 @@ impl INode3D for Game {
         GameSignals::singleton()
             .signals()
             .game_failure()
-            .connect_self(|this| {
+            .connect_self(|this, _reason| {
                 this.signals().game_stopped().emit();
             });
+
+        self.apply_config();
+        config::watch(&mut self.to_gd().upcast());
+        #[cfg(feature = "dev-tools")]
+        crate::dev::install(&mut self.to_gd());
+
+        match hot_reload::take_session() {
+            Some(session) => self.restore_session(&session),
+            None => self.set_state(GameState::Menu),
+        }
+    }
+
+    fn process(&mut self, _delta: f64) {
+        if let Some(time_left) = self.timer.as_ref().map(|timer| timer.get_time_left()) {
+            self.signals().time_left_changed().emit(time_left);
+        }
+        if self.state == GameState::Playing {
+            self.record_rewind_snapshot();
+        }
+    }
+
+    /// Parts and `Game` read keys the GUI left unhandled, so typing into a focused text field never plays
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        if self.state != GameState::Playing {
+            return;
+        }
+        let Some(time) = self.elapsed() else {
+            return;
+        };
+
+        // 只记录零件的按键，暂停不影响回放
+        for (action, _) in Settings::ACTIONS {
+            if action == "pause" {
+                continue;
+            }
+            if event.is_action_pressed(action) {
+                self.replay.push(time, action.into(), true);
+            } else if event.is_action_released(action) {
+                self.replay.push(time, action.into(), false);
+            }
+        }
+
+        if event.is_action_pressed("rewind") {
+            self.rewind();
+        }
     }
 }
 
+#[godot_api]
 impl Game {
+    /// Seconds a practice run lasts, long enough to never end while tuning
+    const PRACTICE_TIME_LIMIT: f64 = 3600.0;
+    /// Seconds of game time between two snapshots kept for a rewind
+    const REWIND_INTERVAL: f64 = 0.25;
+    /// Cards offered by the draft after a won round
+    const DRAFT_SIZE: usize = 3;
+    /// Seconds added to the round when the draft has no new part left to add
+    const ROUND_TIME_STEP: f64 = 10.0;
+
+    /// Seconds left on the round timer, emitted every frame while a run is going
+    #[signal]
+    pub fn time_left_changed(time_left: f64);
+
+    /// A run was stopped and its stats are ready in [`Game::last_run`]
+    #[signal]
+    pub fn run_finished();
+
+    /// Rewinds left in the run, emitted when the run starts and whenever the count changes
+    #[signal]
+    pub fn rewind_charges_changed(charges: i32);
+
+    /// A round was won and [`Game::draft_offer`] holds the cards to pick from
+    #[signal]
+    pub fn draft_offered();
+
+    /// Save the replay of the last finished run, returns the path or an empty string
+    #[func]
+    pub fn save_replay(&self) -> GString {
+        let Some(run) = &self.last_run else {
+            return GString::new();
+        };
+        let name = format!("run_{}_{}", run.record.level, run.record.finished_at as i64);
+        run.replay.save(&name).unwrap_or_default()
+    }
+
+    fn set_state(&mut self, state: GameState) {
+        info!("game state: {:?} -> {:?}", self.state, state);
+        self.state = state;
+        GameSignals::singleton()
+            .signals()
+            .state_changed()
+            .emit(state);
+    }
+
+    fn select_level(&mut self, level: i32) {
+        if !self.load_level(level) {
+            self.set_state(GameState::LevelSelect);
+            return;
+        }
+        self.mode = GameMode::Normal;
+        self.request_game();
+    }
+
+    fn select_tutorial_step(&mut self, step: i32) {
+        let Some(config) = tutorial::STEPS.get(step as usize) else {
+            error!("Unknown tutorial step {}", step);
+            self.set_state(GameState::Menu);
+            return;
+        };
+        self.mode = GameMode::Tutorial;
+        self.time_limit = tutorial::STEP_TIME_LIMIT;
+        self.place_parts(&[config.part], |_| Vector3::ZERO);
+        self.request_game();
+    }
+
+    fn select_practice_part(&mut self, part_id: i32) {
+        let Some(path) = part_scene(part_id) else {
+            error!("Unknown part {}", part_id);
+            self.set_state(GameState::Menu);
+            return;
+        };
+        self.mode = GameMode::Practice;
+        self.time_limit = Self::PRACTICE_TIME_LIMIT;
+        self.place_parts(&[path], |_| Vector3::ZERO);
+        self.request_game();
+    }
+
+    /// Replace the parts under `Parts` with the ones of `level`
+    fn load_level(&mut self, level: i32) -> bool {
+        let Some(config) = Level::get(level) else {
+            error!("Unknown level {}", level);
+            return false;
+        };
+        self.level = level;
+        self.time_limit = config.time_limit;
+        self.clear_parts();
+        let mut parts = self.base().get_node_as::<Node>("Parts");
+        if !site::build(&mut parts, level) {
+            self.place_parts(config.parts, |index| config.part_position(index));
+        }
+        // 换关卡就是一轮新的挑战，卡片不再保留
+        self.cards.clear();
+        self.round = 0;
+
+        GameSignals::singleton()
+            .signals()
+            .level_loaded()
+            .emit(level);
+        true
+    }
+
+    /// Replace the children of `Parts` with new instances of the part scenes
+    fn place_parts(&mut self, scenes: &[&str], position: impl Fn(usize) -> Vector3) {
+        self.clear_parts();
+        let mut parts = self.base().get_node_as::<Node>("Parts");
+        for (index, path) in scenes.iter().enumerate() {
+            let mut part = load::<PackedScene>(*path).instantiate_as::<Node3D>();
+            part.set_position(position(index));
+            parts.add_child(&part);
+            config::apply_params(&mut part.upcast());
+        }
+    }
+
+    /// Free every part and prop under `Parts`
+    fn clear_parts(&mut self) {
+        let mut parts = self.base().get_node_as::<Node>("Parts");
+        for part in parts.get_children().iter_shared() {
+            parts.remove_child(&part);
+            // 立即释放，queue_free 的零件在这一帧里还连着 GameSignals，没有倒计时的话会收到 game_started
+            part.free();
+        }
+    }
+
+    /// Push the config table parameters into the game and the placed parts
+    fn apply_config(&mut self) {
+        for (property, value) in config::params("Game") {
+            match property.as_str() {
+                "countdown" => self.countdown = value as i32,
+                _ => warn!("Game has no config parameter {}", property),
+            }
+        }
+        // 对局中卡片改过的属性先还原，写入新数值后再叠加卡片效果，结束时恢复的就是新数值
+        let modified = !self.modifiers.is_empty();
+        self.modifiers.revert();
+        for mut part in self
+            .base()
+            .get_node_as::<Node>("Parts")
+            .get_children()
+            .iter_shared()
+        {
+            config::apply_params(&mut part);
+        }
+        if modified {
+            let parts = self
+                .parts()
+                .into_iter()
+                .map(|part| part.into_gd())
+                .collect::<Vec<_>>();
+            self.modifiers.apply(&self.cards, &parts);
+        }
+    }
+
+    fn request_game(&mut self) {
+        self.set_state(GameState::Countdown);
+        // 没有倒计时也要发出，零件、音效和教程都靠它做开局前的准备
+        GameSignals::singleton()
+            .signals()
+            .countdown_started()
+            .emit();
+        // 练习模式会频繁重开，不需要倒计时
+        if self.countdown <= 0 || self.mode == GameMode::Practice {
+            self.emit_game_started();
+            return;
+        }
+
+        self.countdown_left = self.countdown;
+        let seconds_left = self.countdown_left as i64;
+        GameSignals::singleton()
+            .signals()
+            .countdown_tick()
+            .emit(seconds_left);
+        self.countdown_timer.start();
+    }
+
+    fn on_countdown_timeout(&mut self) {
+        self.countdown_left -= 1;
+        if self.countdown_left > 0 {
+            let seconds_left = self.countdown_left as i64;
+            GameSignals::singleton()
+                .signals()
+                .countdown_tick()
+                .emit(seconds_left);
+            return;
+        }
+
+        self.countdown_timer.stop();
+        self.emit_game_started();
+    }
+
+    fn emit_game_started(&mut self) {
+        // Game::start 会在信号里重入，先让出当前的可变借用
+        let _guard = self.base_mut();
+        GameSignals::singleton().signals().game_started().emit();
+    }
+
     fn start(&mut self) {
+        self.run_seed = if self.seed != 0 {
+            self.seed
+        } else {
+            randomize();
+            randi()
+        };
+        GameSignals::singleton()
+            .bind()
+            .rng
+            .clone()
+            .set_seed(self.run_seed as u64);
+        self.set_state(GameState::Playing);
+        self.difficulty = Settings::singleton().bind().difficulty;
+        self.outcome = RunOutcome::Aborted;
+        self.failure_reason = GString::new();
+        self.replay = Replay::new(self.mode, self.level, self.difficulty, self.run_seed);
+        self.rewind_buffer.clear();
+        self.rewind_charges = 0;
+        let mut extra_lives = 0;
+        if self.mode == GameMode::Normal {
+            let level_rewinds = Level::get(self.level).map_or(0, |level| level.rewinds);
+            self.rewind_charges = level_rewinds + Card::rewinds(&self.cards);
+            extra_lives = Card::extra_lives(&self.cards);
+            let parts = self
+                .parts()
+                .into_iter()
+                .map(|part| part.into_gd())
+                .collect::<Vec<_>>();
+            self.modifiers.apply(&self.cards, &parts);
+        }
+        GameSignals::singleton().bind_mut().extra_lives = extra_lives;
+        let charges = self.rewind_charges;
+        self.signals().rewind_charges_changed().emit(charges);
+
         let mut timer = Timer::new_alloc();
-        timer.set_wait_time(self.time_limit);
+        timer.set_wait_time(self.time_limit * self.difficulty.time_limit_scale());
         timer.set_one_shot(true);
         timer.set_autostart(true);
         self.base_mut().add_child(&timer);
@@ -55,9 +517,434 @@ impl Game {
     }
 
     fn stop(&mut self) {
-        if let Some(timer) = &self.timer {
-            timer.clone().free();
-            self.timer = None;
+        self.countdown_timer.stop();
+
+        let Some(timer) = self.timer.take() else {
+            return;
+        };
+        let duration = timer.get_wait_time() - timer.get_time_left();
+        timer.free();
+        self.rewind_buffer.clear();
+        self.modifiers.revert();
+        GameSignals::singleton().bind_mut().extra_lives = 0;
+
+        let parts = self.parts();
+        self.score = parts.iter().map(|part| part.dyn_bind().score()).sum();
+        info!("run score: {}", self.score);
+        let closest_calls = parts
+            .iter()
+            .map(|part| {
+                let part = part.dyn_bind();
+                (part.title(), part.closest_call())
+            })
+            .collect();
+
+        let record = RunRecord {
+            mode: self.mode,
+            level: self.level,
+            difficulty: self.difficulty,
+            seed: self.run_seed,
+            score: self.score,
+            duration,
+            outcome: self.outcome,
+            failure_reason: self.failure_reason.clone(),
+            finished_at: Time::singleton().get_unix_time_from_system(),
+        };
+        if self.mode == GameMode::Normal {
+            RunHistory::record(record.clone());
+        }
+        self.last_run = Some(RunSummary {
+            record,
+            closest_calls,
+            replay: std::mem::take(&mut self.replay),
+        });
+
+        self.draft_offer = match (self.mode, self.outcome) {
+            (GameMode::Normal, RunOutcome::Victory) => self.draw_cards(),
+            _ => Vec::new(),
+        };
+
+        // 结算界面会在信号里读取 last_run，先让出当前的可变借用
+        let game = self.to_gd();
+        let has_draft = !self.draft_offer.is_empty();
+        let _guard = self.base_mut();
+        game.signals().run_finished().emit();
+        if has_draft {
+            game.signals().draft_offered().emit();
+        }
+    }
+
+    /// Draw `DRAFT_SIZE` different cards with the run RNG, weighted by card weight
+    fn draw_cards(&self) -> Vec<Card> {
+        let mut pool = Card::all();
+        let mut rng = GameSignals::singleton().bind().rng.clone();
+        let mut offer = Vec::new();
+        while offer.len() < Self::DRAFT_SIZE && !pool.is_empty() {
+            let weights = pool
+                .iter()
+                .map(|card| card.weight.max(0) as f32)
+                .collect::<PackedFloat32Array>();
+            let Ok(index) = usize::try_from(rng.rand_weighted(&weights)) else {
+                break;
+            };
+            offer.push(pool.remove(index));
+        }
+        offer
+    }
+
+    /// Cards to pick from after the round that was just won
+    pub fn draft_offer(&self) -> &[Card] {
+        &self.draft_offer
+    }
+
+    pub fn round(&self) -> i32 {
+        self.round
+    }
+
+    /// Keep the picked card and start the next, harder round
+    fn on_card_picked(&mut self, name: GString) {
+        let Some(index) = self
+            .draft_offer
+            .iter()
+            .position(|card| card.name == name.to_string())
+        else {
+            warn!("Card {} was not offered", name);
+            return;
+        };
+        let card = self.draft_offer.swap_remove(index);
+        self.draft_offer.clear();
+        info!("card picked: {}", card.name);
+        self.cards.push(card);
+        self.round += 1;
+        self.add_round_part();
+        self.request_game();
+    }
+
+    /// Add the next part of `DRAFT_PARTS` that is not placed yet, or lengthen the round once all are
+    fn add_round_part(&mut self) {
+        let scenes = self
+            .parts()
+            .iter()
+            .map(|part| part.clone().into_gd().get_scene_file_path())
+            .collect::<Vec<_>>();
+        match DRAFT_PARTS
+            .iter()
+            .find(|path| !scenes.iter().any(|scene| scene.to_string() == **path))
+        {
+            Some(path) => {
+                info!("round {} adds {}", self.round, path);
+                self.spawn_scene(path);
+            }
+            None => {
+                self.time_limit += Self::ROUND_TIME_STEP;
+                info!("round {} lasts {}s", self.round, self.time_limit);
+            }
+        }
+    }
+
+    /// Stats of the last finished run
+    pub fn last_run(&self) -> Option<&RunSummary> {
+        self.last_run.as_ref()
+    }
+
+    /// Whether a run is going, paused runs included
+    pub fn is_running(&self) -> bool {
+        self.timer.is_some()
+    }
+
+    /// Seconds left on the round timer, `None` outside a run
+    pub fn time_left(&self) -> Option<f64> {
+        self.timer.as_ref().map(|timer| timer.get_time_left())
+    }
+
+    #[cfg(feature = "dev-tools")]
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    /// Every part placed under the `Parts` node
+    pub fn parts(&self) -> Vec<DynGd<Node, dyn Part>> {
+        self.base()
+            .get_node_as::<Node>("Parts")
+            .get_children()
+            .iter_shared()
+            .filter_map(|child| child.try_dynify::<dyn Part>().ok())
+            .collect()
+    }
+
+    /// Add one more part next to the current ones, it starts with the next run
+    #[cfg(feature = "dev-tools")]
+    pub fn spawn_part(&mut self, part_id: i32) -> Result<(), String> {
+        let path = part_scene(part_id).ok_or(format!("未知零件 {part_id}"))?;
+        self.spawn_scene(path);
+        Ok(())
+    }
+
+    /// Place a part scene to the right of the rightmost part
+    fn spawn_scene(&mut self, path: &str) {
+        let right = self
+            .parts()
+            .iter()
+            .map(|part| part.clone().into_gd().cast::<Node3D>().get_position().x)
+            .reduce(f32::max);
+        let x = right.map_or(0.0, |right| right + Level::SPACING);
+        let mut parts = self.base().get_node_as::<Node>("Parts");
+        let mut part = load::<PackedScene>(path).instantiate_as::<Node3D>();
+        part.set_position(Vector3::new(x, 0.0, 0.0));
+        parts.add_child(&part);
+        config::apply_params(&mut part.upcast());
+    }
+
+    /// Snapshot of the run in progress, `None` outside a run
+    pub fn snapshot(&self) -> Option<RunSnapshot> {
+        let time_left = self.time_left()?;
+        let parts = self
+            .parts()
+            .iter()
+            .map(|part| {
+                let node = part.clone().into_gd().cast::<Node3D>();
+                PartSnapshot {
+                    scene: node.get_scene_file_path(),
+                    position: node.get_position(),
+                    rotation: node.get_rotation_degrees(),
+                    state: part.dyn_bind().save_state(),
+                }
+            })
+            .collect();
+
+        Some(RunSnapshot {
+            mode: self.mode,
+            level: self.level,
+            difficulty: self.difficulty,
+            time_limit: self.time_limit,
+            run_seed: self.run_seed,
+            time_left,
+            time_scale: Engine::singleton().get_time_scale(),
+            rng_state: GameSignals::singleton().bind().rng.get_state(),
+            rewind_charges: self.rewind_charges,
+            extra_lives: GameSignals::singleton().bind().extra_lives,
+            cards: self
+                .cards
+                .iter()
+                .map(|card| GString::from(card.name.as_str()))
+                .collect(),
+            round: self.round,
+            parts,
+        })
+    }
+
+    /// Continue a run from a snapshot, the run in progress is dropped without being recorded
+    pub fn restore_snapshot(&mut self, snapshot: &RunSnapshot) {
+        self.countdown_timer.stop();
+        if let Some(timer) = self.timer.take() {
+            timer.free();
+        }
+        self.modifiers.revert();
+        self.mode = snapshot.mode;
+        self.level = snapshot.level;
+        self.time_limit = snapshot.time_limit;
+        let scenes = snapshot
+            .parts
+            .iter()
+            .map(|part| part.scene.to_string())
+            .collect::<Vec<_>>();
+        let scenes = scenes.iter().map(String::as_str).collect::<Vec<_>>();
+        self.place_parts(&scenes, |index| snapshot.parts[index].position);
+        for (part, saved) in self.parts().into_iter().zip(&snapshot.parts) {
+            part.into_gd()
+                .cast::<Node3D>()
+                .set_rotation_degrees(saved.rotation);
+        }
+        let mut parts = self.base().get_node_as::<Node>("Parts");
+        site::build_props(&mut parts, snapshot.level);
+        self.cards = snapshot
+            .cards
+            .as_slice()
+            .iter()
+            .filter_map(|name| Card::get(&name.to_string()))
+            .collect();
+        self.round = snapshot.round;
+        self.draft_offer.clear();
+
+        {
+            let _guard = self.base_mut();
+            GameSignals::singleton()
+                .signals()
+                .countdown_started()
+                .emit();
+        }
+        // 用原来的种子开始，记录和回放里的种子保持一致
+        let seed = std::mem::replace(&mut self.seed, snapshot.run_seed);
+        self.emit_game_started();
+        self.seed = seed;
+
+        self.difficulty = snapshot.difficulty;
+        self.replay.difficulty = snapshot.difficulty;
+        let wait_time = self.time_limit * self.difficulty.time_limit_scale();
+        if let Some(timer) = &mut self.timer {
+            timer.set_wait_time(wait_time);
         }
+        GameSignals::singleton().bind_mut().extra_lives = snapshot.extra_lives;
+        self.rewind_charges = snapshot.rewind_charges;
+        let charges = self.rewind_charges;
+        self.signals().rewind_charges_changed().emit(charges);
+        self.apply_snapshot(snapshot);
+        info!("run restored with {:.1}s left", snapshot.time_left);
+    }
+
+    /// Put the round timer, RNG, time scale and parts of the run in progress back to a snapshot
+    fn apply_snapshot(&mut self, snapshot: &RunSnapshot) {
+        if let Some(timer) = &mut self.timer {
+            resume_timer(timer, snapshot.time_left);
+        }
+        GameSignals::singleton()
+            .bind()
+            .rng
+            .clone()
+            .set_state(snapshot.rng_state);
+
+        let placed = self.parts();
+        {
+            // 零件恢复时会发出信号，先让出当前的可变借用
+            let _guard = self.base_mut();
+            for (mut part, saved) in placed.into_iter().zip(&snapshot.parts) {
+                part.dyn_bind_mut().restore_state(&saved.state);
+            }
+        }
+        Engine::singleton().set_time_scale(snapshot.time_scale);
+    }
+
+    /// Give the run in progress more rewinds, for cards and other rewards
+    #[func]
+    pub fn grant_rewinds(&mut self, count: i32) {
+        if !self.is_running() {
+            return;
+        }
+        self.rewind_charges += count;
+        let charges = self.rewind_charges;
+        self.signals().rewind_charges_changed().emit(charges);
+    }
+
+    /// Cards held by the player
+    #[cfg(feature = "dev-tools")]
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    /// Take a card by name, its effects apply from the next run, returns false for unknown cards
+    #[func]
+    pub fn add_card(&mut self, name: GString) -> bool {
+        let Some(card) = Card::get(&name.to_string()) else {
+            warn!("Unknown card {}", name);
+            return false;
+        };
+        info!("card taken: {}", card.name);
+        self.cards.push(card);
+        true
     }
-}
\ No newline at end of file
+
+    /// Drop every card held
+    #[func]
+    pub fn clear_cards(&mut self) {
+        self.cards.clear();
+    }
+
+    /// Keep a snapshot every `REWIND_INTERVAL` seconds of game time, dropping those too old to rewind to
+    fn record_rewind_snapshot(&mut self) {
+        let Some(time_left) = self.time_left() else {
+            return;
+        };
+        let due = self
+            .rewind_buffer
+            .back()
+            .is_none_or(|last| last.time_left - time_left >= Self::REWIND_INTERVAL);
+        if !due {
+            return;
+        }
+        let Some(snapshot) = self.snapshot() else {
+            return;
+        };
+        self.rewind_buffer.push_back(snapshot);
+        while self
+            .rewind_buffer
+            .front()
+            .is_some_and(|oldest| oldest.time_left - time_left > self.rewind_seconds)
+        {
+            self.rewind_buffer.pop_front();
+        }
+    }
+
+    /// Roll the run back by up to `rewind_seconds`, using one charge
+    fn rewind(&mut self) {
+        if self.rewind_charges <= 0 || self.rewind_buffer.is_empty() {
+            return;
+        }
+        self.rewind_charges -= 1;
+        let charges = self.rewind_charges;
+        self.signals().rewind_charges_changed().emit(charges);
+
+        let seconds = self.roll_back().unwrap_or_default();
+        info!("rewound {:.1}s, {} rewinds left", seconds, charges);
+    }
+
+    /// Put the run back to the oldest buffered snapshot, returns the seconds rolled back
+    fn roll_back(&mut self) -> Option<f64> {
+        let snapshot = self.rewind_buffer.pop_front()?;
+        self.rewind_buffer.clear();
+        let seconds = snapshot.time_left - self.time_left().unwrap_or(snapshot.time_left);
+        self.apply_snapshot(&snapshot);
+        Some(seconds)
+    }
+
+    /// An extra life took the failure, roll back to before it, or fail after all if there is nothing to roll back to
+    fn on_life_lost(&mut self, reason: GString, lives_left: i32) {
+        if !self.is_running() {
+            return;
+        }
+        match self.roll_back() {
+            Some(seconds) => info!(
+                "extra life used, rolled back {:.1}s, {} lives left",
+                seconds, lives_left
+            ),
+            None => {
+                let _guard = self.base_mut();
+                GameSignals::singleton()
+                    .signals()
+                    .game_failure()
+                    .emit(&reason);
+            }
+        }
+    }
+
+    /// State of the session, enough for `restore_session` to continue the run in progress
+    pub fn save_session(&self) -> Dictionary {
+        let mut session = vdict! { "state": self.state };
+        if let Some(snapshot) = self.snapshot() {
+            session.set("snapshot", snapshot.to_dictionary());
+        }
+        session
+    }
+
+    /// Continue a session saved by `save_session`, runs that can't be continued go back to the menu
+    fn restore_session(&mut self, session: &Dictionary) {
+        let state: GameState = state_value(session, "state");
+        let snapshot =
+            RunSnapshot::from_dictionary(&state_value::<Dictionary>(session, "snapshot"));
+        match snapshot {
+            Some(snapshot) if snapshot.mode == GameMode::Normal => {
+                // 暂停菜单的状态没有保留，继续时不再暂停
+                self.base().get_tree().unwrap().set_pause(false);
+                self.restore_snapshot(&snapshot);
+            }
+            _ if state == GameState::LevelSelect => self.set_state(state),
+            _ => self.set_state(GameState::Menu),
+        }
+    }
+
+    /// Game time since the run started, `None` outside a run
+    fn elapsed(&self) -> Option<f64> {
+        self.timer
+            .as_ref()
+            .map(|timer| timer.get_wait_time() - timer.get_time_left())
+    }
+}
@@ -1,6 +1,8 @@
-use godot::classes::Engine;
+use crate::game_controller::GameState;
+use godot::classes::{Engine, RandomNumberGenerator};
 use godot::prelude::*;
 use godot_tokio::AsyncRuntime;
+use log::info;
 use std::time::Duration;
 
 /// GameSignals is a singleton that manages global game events
@@ -9,16 +11,27 @@ use std::time::Duration;
 pub struct GameSignals {
     // Base node fields
     base: Base<Node>,
+    /// While set, part failures are reported through `failure_forgiven` instead of ending the run
+    pub failures_suppressed: bool,
+    /// Failures the run survives, reported through `life_lost`, set by `Game` from the cards of the run
+    pub extra_lives: i32,
+    /// RNG of the run, seeded by `Game` so a run can be replayed and snapshotted
+    pub rng: Gd<RandomNumberGenerator>,
 }
 
 #[godot_api]
 impl INode for GameSignals {
     fn init(base: Base<Node>) -> Self {
-        Self { base }
+        Self {
+            base,
+            failures_suppressed: false,
+            extra_lives: 0,
+            rng: RandomNumberGenerator::new_gd(),
+        }
     }
 
     fn ready(&mut self) {
-        godot_print!("GameSignals singleton ready");
+        info!("GameSignals singleton ready");
     }
 }
 
@@ -32,6 +45,59 @@ impl GameSignals {
         }
     }
 
+    /// The game state machine moved to a new state, screens show or hide themselves from it
+    #[signal]
+    pub fn state_changed(state: GameState);
+
+    /// The player asked to pick a level
+    #[signal]
+    pub fn level_select_requested();
+
+    /// The player picked a level to play
+    #[signal]
+    pub fn level_selected(level: i32);
+
+    /// The player asked to play a tutorial step, counted from 0
+    #[signal]
+    pub fn tutorial_requested(step: i32);
+
+    /// The player asked to practice a single part, by part id
+    #[signal]
+    pub fn practice_requested(part_id: i32);
+
+    /// A part failed while failures were suppressed, the run goes on
+    #[signal]
+    pub fn failure_forgiven(reason: GString);
+
+    /// A part failed and an extra life was used up instead of ending the run
+    #[signal]
+    pub fn life_lost(reason: GString, lives_left: i32);
+
+    /// The player picked a card in the draft after a won round, by card name
+    #[signal]
+    pub fn card_picked(name: GString);
+
+    /// The player asked to play the level after the current one
+    #[signal]
+    pub fn next_level_requested();
+
+    /// The parts of a level were placed under `Parts`
+    #[signal]
+    pub fn level_loaded(level: i32);
+
+    /// A new run was requested, `Game` answers with the countdown and then `game_started`
+    #[signal]
+    pub fn game_requested();
+
+    /// The pre-game countdown began, parts show their idle visuals; emitted before every
+    /// `game_started`, also when the run starts without a countdown
+    #[signal]
+    pub fn countdown_started();
+
+    /// Whole seconds left before the run starts
+    #[signal]
+    pub fn countdown_tick(seconds_left: i64);
+
     #[signal]
     pub fn game_started();
 
@@ -39,23 +105,59 @@ impl GameSignals {
     pub fn game_victory();
 
     #[signal]
-    pub fn game_failure();
+    pub fn game_failure(reason: GString);
 
     #[signal]
     pub fn game_stopped();
+
+    /// The run was frozen by the pause menu
+    #[signal]
+    pub fn game_paused();
+
+    /// The run continues after a pause
+    #[signal]
+    pub fn game_resumed();
+
+    /// The player asked to leave the run and go back to the main menu
+    #[signal]
+    pub fn menu_requested();
+
+    /// The player asked to open the settings
+    #[signal]
+    pub fn settings_requested();
+
+    /// The player asked to see the high scores and run history
+    #[signal]
+    pub fn history_requested();
+
+    /// The config tables were read again, nodes should pick up the new values
+    #[signal]
+    pub fn config_reloaded();
 }
 
 impl GameSignals {
     /// godot中，同一帧内调用信号会导致循环引用，等待一下
-    pub fn emit_game_failure(&mut self) {
-        godot::task::spawn(async {
+    pub fn emit_game_failure(&mut self, reason: &str) {
+        let reason = GString::from(reason);
+        godot::task::spawn(async move {
             AsyncRuntime::runtime()
                 .spawn(async {
                     tokio::time::sleep(Duration::from_micros(1)).await;
                 })
                 .await
                 .unwrap();
-            GameSignals::singleton().signals().game_failure().emit()
+            let mut signals = GameSignals::singleton();
+            if signals.bind().failures_suppressed {
+                info!("failure forgiven: {}", reason);
+                signals.signals().failure_forgiven().emit(&reason)
+            } else if signals.bind().extra_lives > 0 {
+                signals.bind_mut().extra_lives -= 1;
+                let lives_left = signals.bind().extra_lives;
+                info!("life lost: {}, {} left", reason, lives_left);
+                signals.signals().life_lost().emit(&reason, lives_left)
+            } else {
+                signals.signals().game_failure().emit(&reason)
+            }
         });
     }
 }
@@ -0,0 +1,59 @@
+/*!
+gdextension 热重载：卸载前把当前局的状态存进 Engine 的 meta，Engine 不属于本扩展，卸载后仍然存在
+用 Rust 闭包连接的信号在卸载后会失效，所以重新加载后要重建整个场景，`Game` 在 ready 时取回状态继续这一局
+只有普通模式的对局会继续，教程和练习回到主菜单
+*/
+
+use crate::game_controller::Game;
+use godot::classes::{Engine, SceneTree};
+use godot::prelude::*;
+use log::info;
+
+const SESSION_META: &str = "soul_game_session";
+
+/// Keep the state of the running scene before the extension is unloaded
+pub fn save_session() {
+    let Some(game) = current_game() else {
+        return;
+    };
+    let session = game.bind().save_session();
+    Engine::singleton().set_meta(SESSION_META, &session.to_variant());
+    info!("session saved for hot reload");
+}
+
+/// After a hot reload, rebuild the current scene with the new classes
+pub fn reload_scene() {
+    if !Engine::singleton().has_meta(SESSION_META) {
+        return;
+    }
+    let Some(mut tree) = scene_tree() else {
+        return;
+    };
+    info!("reloading the current scene after hot reload");
+    tree.call_deferred("reload_current_scene", &[]);
+}
+
+/// The session kept by `save_session`, only once
+pub fn take_session() -> Option<Dictionary> {
+    let mut engine = Engine::singleton();
+    if !engine.has_meta(SESSION_META) {
+        return None;
+    }
+    let session = engine.get_meta(SESSION_META).try_to().ok();
+    engine.remove_meta(SESSION_META);
+    session
+}
+
+fn current_game() -> Option<Gd<Game>> {
+    if Engine::singleton().is_editor_hint() {
+        return None;
+    }
+    scene_tree()?.get_current_scene()?.try_cast::<Game>().ok()
+}
+
+fn scene_tree() -> Option<Gd<SceneTree>> {
+    Engine::singleton()
+        .get_main_loop()?
+        .try_cast::<SceneTree>()
+        .ok()
+}
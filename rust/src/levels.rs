@@ -0,0 +1,150 @@
+/*!
+关卡列表：每关的时限和需要放置的零件
+第一关总是解锁的，之后每关需要在普通模式下通关上一关才能解锁
+*/
+
+use crate::game_controller::GameMode;
+use crate::records::{RunHistory, RunOutcome};
+use godot::prelude::*;
+
+pub struct Level {
+    pub id: i32,
+    pub name: &'static str,
+    /// Round length in seconds before the difficulty multiplier
+    pub time_limit: f64,
+    /// Part scenes placed under `Parts`, left to right
+    pub parts: &'static [&'static str],
+    /// Rewind charges the player starts the level with
+    pub rewinds: i32,
+}
+
+const RED: &str = "res://parts/Button1001.tscn";
+const YELLOW: &str = "res://parts/Button1002.tscn";
+const BLUE: &str = "res://parts/Button1003.tscn";
+const PINK: &str = "res://parts/Button1005.tscn";
+
+/// Parts added one per round by the card draft, easiest first
+pub const DRAFT_PARTS: [&str; 4] = [BLUE, RED, YELLOW, PINK];
+
+pub const LEVELS: [Level; 4] = [
+    Level {
+        id: 1,
+        name: "蓝色按钮",
+        time_limit: 30.0,
+        parts: &[BLUE],
+        rewinds: 0,
+    },
+    Level {
+        id: 2,
+        name: "红与蓝",
+        time_limit: 30.0,
+        parts: &[RED, BLUE],
+        rewinds: 0,
+    },
+    Level {
+        id: 3,
+        name: "三个按钮",
+        time_limit: 40.0,
+        parts: &[RED, YELLOW, BLUE],
+        rewinds: 1,
+    },
+    Level {
+        id: 4,
+        name: "全部按钮",
+        time_limit: 45.0,
+        parts: &[RED, YELLOW, BLUE, PINK],
+        rewinds: 1,
+    },
+];
+
+impl Level {
+    /// Distance between two neighbouring parts
+    pub const SPACING: f32 = 1.5;
+
+    pub fn get(id: i32) -> Option<&'static Level> {
+        LEVELS.iter().find(|level| level.id == id)
+    }
+
+    /// Position of the part at `index`, the row is centred on the origin
+    pub fn part_position(&self, index: usize) -> Vector3 {
+        Self::row_position(self.parts.len(), index)
+    }
+
+    /// Position of the part at `index` in a centred row of `count` parts
+    pub fn row_position(count: usize, index: usize) -> Vector3 {
+        let offset = (count as f32 - 1.0) / 2.0;
+        Vector3::new((index as f32 - offset) * Self::SPACING, 0.0, 0.0)
+    }
+
+    pub fn is_unlocked(&self, history: &RunHistory) -> bool {
+        self.is_unlocked_by(
+            history
+                .runs
+                .iter()
+                .map(|run| (run.mode, run.level, run.outcome)),
+        )
+    }
+
+    /// Whether one of `runs`, given as (mode, level, outcome), unlocks this level
+    fn is_unlocked_by(&self, mut runs: impl Iterator<Item = (GameMode, i32, RunOutcome)>) -> bool {
+        self.id == 1
+            || runs.any(|(mode, level, outcome)| {
+                mode == GameMode::Normal && level == self.id - 1 && outcome == RunOutcome::Victory
+            })
+    }
+
+    pub fn is_last(&self) -> bool {
+        Level::get(self.id + 1).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unlocked(id: i32, runs: &[(GameMode, i32, RunOutcome)]) -> bool {
+        Level::get(id).unwrap().is_unlocked_by(runs.iter().copied())
+    }
+
+    #[test]
+    fn first_level_is_always_unlocked() {
+        assert!(unlocked(1, &[]));
+    }
+
+    #[test]
+    fn level_unlocks_after_a_normal_victory_on_the_previous_one() {
+        assert!(!unlocked(2, &[]));
+        assert!(unlocked(2, &[(GameMode::Normal, 1, RunOutcome::Victory)]));
+    }
+
+    #[test]
+    fn other_runs_do_not_unlock() {
+        let runs = [
+            (GameMode::Normal, 1, RunOutcome::Failure),
+            (GameMode::Normal, 1, RunOutcome::Aborted),
+            (GameMode::Practice, 1, RunOutcome::Victory),
+            (GameMode::Tutorial, 1, RunOutcome::Victory),
+            (GameMode::Normal, 2, RunOutcome::Victory),
+        ];
+        assert!(!unlocked(2, &runs));
+    }
+
+    #[test]
+    fn row_is_centred_on_the_origin() {
+        assert_eq!(Level::row_position(1, 0), Vector3::ZERO);
+        assert_eq!(Level::row_position(2, 0), Vector3::new(-0.75, 0.0, 0.0));
+        assert_eq!(Level::row_position(2, 1), Vector3::new(0.75, 0.0, 0.0));
+        assert_eq!(Level::row_position(3, 1), Vector3::ZERO);
+    }
+
+    #[test]
+    fn row_keeps_the_spacing_between_parts() {
+        for count in 2..=5 {
+            for index in 1..count {
+                let step =
+                    Level::row_position(count, index) - Level::row_position(count, index - 1);
+                assert_eq!(step, Vector3::new(Level::SPACING, 0.0, 0.0));
+            }
+        }
+    }
+}
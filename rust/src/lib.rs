@@ -1,46 +1,54 @@
+mod audio;
+mod cards;
+mod config;
+#[cfg(feature = "dev-tools")]
+mod dev;
 mod game_controller;
 mod game_signals;
+mod hot_reload;
+mod levels;
+mod logging;
 mod parts;
+mod records;
+mod replay;
+mod settings;
+mod site;
+mod snapshot;
+mod tutorial;
 mod ui;
 
 use crate::game_signals::GameSignals;
+use crate::settings::Settings;
 use godot::classes::Engine;
 use godot::prelude::*;
 use godot_tokio::AsyncRuntime;
+use log::{error, warn};
 
-#[macro_export]
-macro_rules! godot_print_err {
-    ($fmt:literal $(, $args:expr)* $(,)?) => {
-        godot::global::printerr(&[
-            godot::builtin::Variant::from(
-                format!($fmt $(, $args)*)
-            )
-        ])
-    };
-}
 struct MyExtension;
 
 #[gdextension]
 unsafe impl ExtensionLibrary for MyExtension {
     fn on_level_init(level: InitLevel) {
-        match level {
-            InitLevel::Scene => {
-                let mut engine = Engine::singleton();
-                Self::register_async_runtime(&mut engine);
-                Self::register_game_signals(&mut engine);
+        if level == InitLevel::Scene {
+            logging::init();
+            if let Err(error) = config::load() {
+                error!("Failed to load config tables: {}", error);
             }
-            _ => (),
+            let mut engine = Engine::singleton();
+            Self::register_async_runtime(&mut engine);
+            Self::register_game_signals(&mut engine);
+            Self::register_settings(&mut engine);
+            hot_reload::reload_scene();
         }
     }
 
     fn on_level_deinit(level: InitLevel) {
-        match level {
-            InitLevel::Scene => {
-                let mut engine = Engine::singleton();
-                Self::unregister_async_runtime(&mut engine);
-                Self::unregister_game_signals(&mut engine);
-            }
-            _ => (),
+        if level == InitLevel::Scene {
+            hot_reload::save_session();
+            let mut engine = Engine::singleton();
+            Self::unregister_async_runtime(&mut engine);
+            Self::unregister_game_signals(&mut engine);
+            Self::unregister_settings(&mut engine);
         }
     }
 }
@@ -54,12 +62,20 @@ impl MyExtension {
         engine.register_singleton(GameSignals::SINGLETON, &GameSignals::new_alloc());
     }
 
+    /// Load the user preferences and apply them before the first scene is ready
+    fn register_settings(engine: &mut Gd<Engine>) {
+        let mut settings = Settings::new_alloc();
+        settings.bind_mut().load();
+        settings.bind_mut().apply();
+        engine.register_singleton(Settings::SINGLETON, &settings);
+    }
+
     fn unregister_async_runtime(engine: &mut Gd<Engine>) {
         if let Some(async_singleton) = engine.get_singleton(AsyncRuntime::SINGLETON) {
             engine.unregister_singleton(AsyncRuntime::SINGLETON);
             async_singleton.free();
         } else {
-            godot_print!(
+            warn!(
                 "Failed to find & free singleton -> {}",
                 AsyncRuntime::SINGLETON
             );
@@ -71,10 +87,19 @@ impl MyExtension {
             engine.unregister_singleton(GameSignals::SINGLETON);
             game_signals.free();
         } else {
-            godot_print!(
+            warn!(
                 "Failed to find & free singleton -> {}",
                 GameSignals::SINGLETON
             );
         }
     }
+
+    fn unregister_settings(engine: &mut Gd<Engine>) {
+        if let Some(settings) = engine.get_singleton(Settings::SINGLETON) {
+            engine.unregister_singleton(Settings::SINGLETON);
+            settings.free();
+        } else {
+            warn!("Failed to find & free singleton -> {}", Settings::SINGLETON);
+        }
+    }
 }
@@ -0,0 +1,185 @@
+/*!
+日志：各模块通过 `log` crate 的宏记录日志，这里把它们转发到 Godot 控制台，并可选地写入 `user://logs`
+每个模块（target）可以单独设置级别，发布版本在编译期去掉 debug/trace 级别的日志
+日志文件超过 `MAX_FILE_SIZE` 后轮换，最多保留 `MAX_FILES` 个
+*/
+
+use godot::classes::ProjectSettings;
+use godot::prelude::*;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const LOG_DIR: &str = "user://logs";
+const FILE_NAME: &str = "soul_game";
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+const MAX_FILES: usize = 5;
+
+static LOGGER: GodotLogger = GodotLogger {
+    filters: RwLock::new(Filters {
+        default: LevelFilter::Info,
+        targets: Vec::new(),
+    }),
+    file: Mutex::new(None),
+};
+
+struct Filters {
+    default: LevelFilter,
+    /// Target prefix and its level, the longest matching prefix wins
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filters {
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .filter(|(prefix, _)| target == prefix || target.starts_with(&format!("{prefix}::")))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+}
+
+/// Log file in `user://logs`, rotated by size
+struct LogFile {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(dir: PathBuf) -> std::io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::path(&dir, 0))?;
+        let size = file.metadata()?.len();
+        Ok(Self { dir, file, size })
+    }
+
+    fn path(dir: &Path, index: usize) -> PathBuf {
+        match index {
+            0 => dir.join(format!("{FILE_NAME}.log")),
+            _ => dir.join(format!("{FILE_NAME}.{index}.log")),
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        if self.size + line.len() as u64 > MAX_FILE_SIZE {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{line}")?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    /// soul_game.log -> soul_game.1.log -> ... , the oldest file is dropped
+    fn rotate(&mut self) -> std::io::Result<()> {
+        for index in (0..MAX_FILES - 1).rev() {
+            let from = Self::path(&self.dir, index);
+            if from.exists() {
+                fs::rename(&from, Self::path(&self.dir, index + 1))?;
+            }
+        }
+        self.file = File::create(Self::path(&self.dir, 0))?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+struct GodotLogger {
+    filters: RwLock<Filters>,
+    file: Mutex<Option<LogFile>>,
+}
+
+impl Log for GodotLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let filters = self.filters.read().unwrap();
+        metadata.level() <= filters.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let target = record
+            .target()
+            .strip_prefix("soul_game::")
+            .unwrap_or(record.target());
+        let message = format!("[{}] {}: {}", record.level(), target, record.args());
+        match record.level() {
+            Level::Error => godot_error!("{}", message),
+            Level::Warn => godot_warn!("{}", message),
+            _ => godot_print!("{}", message),
+        }
+
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs_f64())
+                .unwrap_or_default();
+            // 写文件失败时不再记录日志，避免递归
+            let _ = file.write_line(&format!("{time:.3} {message}"));
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+/// Install the logger, safe to call again after a hot reload
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Trace);
+    }
+}
+
+/// Level used for targets without their own level
+pub fn set_default_level(level: LevelFilter) {
+    LOGGER.filters.write().unwrap().default = level;
+}
+
+/// Set the level of a target, e.g. `parts::blue`, the crate name may be left out
+pub fn set_target_level(target: &str, level: LevelFilter) {
+    let target = if target.starts_with("soul_game") {
+        target.to_string()
+    } else {
+        format!("soul_game::{target}")
+    };
+    let mut filters = LOGGER.filters.write().unwrap();
+    filters.targets.retain(|(prefix, _)| *prefix != target);
+    filters.targets.push((target, level));
+}
+
+/// Drop every per-target level
+pub fn clear_target_levels() {
+    LOGGER.filters.write().unwrap().targets.clear();
+}
+
+/// Start or stop writing the log file
+pub fn set_file_enabled(enabled: bool) {
+    let mut file = LOGGER.file.lock().unwrap();
+    if !enabled {
+        *file = None;
+        return;
+    }
+    if file.is_some() {
+        return;
+    }
+
+    let dir = ProjectSettings::singleton()
+        .globalize_path(LOG_DIR)
+        .to_string();
+    match LogFile::open(PathBuf::from(dir)) {
+        Ok(log_file) => *file = Some(log_file),
+        Err(error) => godot_error!("Failed to open log file: {}", error),
+    }
+}
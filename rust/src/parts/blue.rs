@@ -5,13 +5,11 @@
 */
 
 use crate::game_signals::GameSignals;
-use godot::classes::notify::Node3DNotification;
-use godot::classes::{
-    Engine, INode3D, Input, InputEvent, MeshInstance3D, Node3D, ProgressBar, StandardMaterial3D,
-    Time, Timer,
-};
-use godot::global::{Key, randf_range};
+use crate::parts::feedback::DangerFeedback;
+use crate::parts::{Part, Tunable, resume_timer, state_value, timer_time_left, update_danger};
+use godot::classes::{INode3D, Input, InputEvent, MeshInstance3D, Node3D, ProgressBar, Timer};
 use godot::prelude::*;
+use log::debug;
 use std::ops::Range;
 
 #[derive(GodotClass)]
@@ -20,6 +18,8 @@ pub struct BlueButton {
     base: Base<Node3D>,
     #[init(node = "%Pillar")]
     pillar: OnReady<Gd<MeshInstance3D>>,
+    #[init(node = "%DangerFeedback")]
+    feedback: OnReady<Gd<DangerFeedback>>,
     #[init(node = "%ProgressBar")]
     progress_bar: OnReady<Gd<ProgressBar>>,
     /// 自动减少电量
@@ -28,13 +28,34 @@ pub struct BlueButton {
     /// 按下按键时增加电量
     #[init(val = Timer::new_alloc())]
     manual_timer: Gd<Timer>,
-    #[init(val = 0.0..100.0)]
+    #[init(val = Self::BASE_POWER_RANGE)]
     power_range: Range<f64>, // Time limit in seconds
+    /// Width of `power_range` relative to 0..100, widened or narrowed around the middle
+    #[export]
+    #[var(get, set = set_range_scale)]
+    #[init(val = 1.0)]
+    range_scale: f64,
+    #[export]
     #[init(val = 50.0)]
     default_power: f64, // Time limit in seconds
+    #[export]
     #[init(val = 10.0)]
     change_rate: f64, // Time limit in seconds
+    /// 电量处于区间正中时每次结算获得的分数
+    #[export]
+    #[init(val = 10.0)]
+    score_rate: f64,
     power: f64,
+    /// Lowest and highest power reached during the run
+    min_power: f64,
+    max_power: f64,
+    score: f64,
+    danger: f64,
+    /// Seconds after `game_started` before this part becomes active
+    #[export]
+    activation_delay: f64,
+    #[init(val = Timer::new_alloc())]
+    activation_timer: Gd<Timer>,
     active: bool, // Whether the part is currently active
 }
 
@@ -64,10 +85,22 @@ impl INode3D for BlueButton {
         self.progress_bar.set_min(self.power_range.start);
         self.progress_bar.set_max(self.power_range.end);
 
+        self.activation_timer.set_one_shot(true);
+        self.activation_timer
+            .signals()
+            .timeout()
+            .connect_other(self, Self::start);
+        let timer = self.activation_timer.clone();
+        self.base_mut().add_child(&timer);
+
+        GameSignals::singleton()
+            .signals()
+            .countdown_started()
+            .connect_other(self, Self::show_idle);
         GameSignals::singleton()
             .signals()
             .game_started()
-            .connect_other(self, Self::start);
+            .connect_other(self, Self::on_game_started);
 
         GameSignals::singleton()
             .signals()
@@ -75,55 +108,224 @@ impl INode3D for BlueButton {
             .connect_other(self, Self::stop);
     }
 
-    fn input(&mut self, event: Gd<InputEvent>) {
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        if event.is_action_released("blue_button") {
+            self.feedback.bind_mut().release();
+        }
         if !self.active {
             return;
         }
 
         // 检查特定键是否被按下
         if Input::singleton().is_action_just_pressed("blue_button") {
-            godot_print!("blue button pressed");
+            debug!("blue button pressed");
+            self.feedback.bind_mut().press();
             self.manual_timer.start();
+            self.signals().charging_started().emit();
         }
     }
 }
 
+#[godot_api]
 impl BlueButton {
+    const BASE_POWER_RANGE: Range<f64> = 0.0..100.0;
+
+    /// How close the power is to either end of `power_range`, 0..1
+    #[signal]
+    pub fn danger_changed(level: f64);
+
+    /// Current power, emitted on every change including the reset at start
+    #[signal]
+    pub fn power_changed(power: f64);
+
+    /// The key was pressed and power started charging
+    #[signal]
+    pub fn charging_started();
+
+    /// Idle visuals shown during the countdown
+    fn show_idle(&mut self) {
+        self.progress_bar.set_value(self.default_power);
+    }
+
+    fn on_game_started(&mut self) {
+        if self.activation_delay > 0.0 {
+            self.activation_timer.set_wait_time(self.activation_delay);
+            self.activation_timer.start();
+        } else {
+            self.start();
+        }
+    }
+
     fn start(&mut self) {
-        godot_print!("blue button started");
+        debug!("blue button started");
         self.power = self.default_power;
+        self.min_power = self.power;
+        self.max_power = self.power;
+        self.score = 0.0;
         self.progress_bar.set_value(self.default_power);
+        let power = self.power;
+        self.signals().power_changed().emit(power);
+        self.show_power_on_pillar();
 
         self.auto_timer.start();
         self.active = true;
     }
 
     fn stop(&mut self) {
-        godot_print!("blue button stopped");
+        debug!("blue button stopped");
         self.active = false;
+        self.activation_timer.stop();
         self.auto_timer.stop();
         self.manual_timer.stop();
+        self.set_danger(0.0);
     }
 
     fn increase_power(&mut self) {
-        godot_print!("blue button increase power");
+        debug!("blue button increase power");
         self.power += self.change_rate;
         self.update_power();
     }
 
     fn decrease_power(&mut self) {
-        godot_print!("blue button decrease power");
+        debug!("blue button decrease power");
         self.power -= self.change_rate;
         self.update_power();
     }
 
     fn update_power(&mut self) {
-        godot_print!("blue button power: {}", self.power);
-        if self.power >= self.power_range.end || self.power <= self.power_range.start {
-            GameSignals::singleton().bind_mut().emit_game_failure();
+        debug!("blue button power: {}", self.power);
+        self.min_power = self.min_power.min(self.power);
+        self.max_power = self.max_power.max(self.power);
+        let power = self.power;
+        self.signals().power_changed().emit(power);
+        self.show_power_on_pillar();
+        self.set_danger(1.0 - self.balance());
+        if self.power >= self.power_range.end {
+            GameSignals::singleton()
+                .bind_mut()
+                .emit_game_failure("蓝色按钮电量充满了");
+            return;
+        }
+        if self.power <= self.power_range.start {
+            GameSignals::singleton()
+                .bind_mut()
+                .emit_game_failure("蓝色按钮电量耗尽了");
             return;
         }
 
         self.progress_bar.set_value(self.power);
+        self.score += self.score_rate * self.balance();
     }
-}
\ No newline at end of file
+
+    /// 柱子的高度跟随电量
+    fn show_power_on_pillar(&mut self) {
+        let range = self.power_range.end - self.power_range.start;
+        let fraction = (self.power - self.power_range.start) / range;
+        self.feedback.bind_mut().set_fill(fraction);
+    }
+
+    /// 1.0 when power sits in the middle of `power_range`, falling to 0.0 at either end
+    fn balance(&self) -> f64 {
+        let half = (self.power_range.end - self.power_range.start) / 2.0;
+        let middle = self.power_range.start + half;
+        (1.0 - (self.power - middle).abs() / half).clamp(0.0, 1.0)
+    }
+
+    fn set_danger(&mut self, level: f64) {
+        if let Some(level) = update_danger(&mut self.danger, &mut self.feedback, level) {
+            self.signals().danger_changed().emit(level);
+        }
+    }
+
+    /// Set the range scale, the middle of the range stays where it is
+    #[func]
+    pub fn set_range_scale(&mut self, scale: f64) {
+        self.range_scale = scale;
+        let Range { start, end } = Self::BASE_POWER_RANGE;
+        let middle = (start + end) / 2.0;
+        let half = (end - start) / 2.0 * scale;
+        self.power_range = middle - half..middle + half;
+        if self.base().is_node_ready() {
+            self.progress_bar.set_min(self.power_range.start);
+            self.progress_bar.set_max(self.power_range.end);
+        }
+    }
+}
+
+/// Properties the practice panel can tune
+const TUNABLES: [Tunable; 3] = [
+    Tunable::new("change_rate", "每秒电量变化", 1.0, 30.0, 1.0),
+    Tunable::new("default_power", "初始电量", 10.0, 90.0, 5.0),
+    Tunable::new("score_rate", "得分倍率", 1.0, 50.0, 1.0),
+];
+
+#[godot_dyn]
+impl Part for BlueButton {
+    fn score(&self) -> f64 {
+        self.score
+    }
+
+    fn title(&self) -> GString {
+        "蓝色按钮".into()
+    }
+
+    fn tunables(&self) -> &'static [Tunable] {
+        &TUNABLES
+    }
+
+    fn save_state(&self) -> Dictionary {
+        vdict! {
+            "active": self.active,
+            "power": self.power,
+            "min_power": self.min_power,
+            "max_power": self.max_power,
+            "score": self.score,
+            "auto_timer": timer_time_left(&self.auto_timer),
+            "manual_timer": timer_time_left(&self.manual_timer),
+            "activation_timer": timer_time_left(&self.activation_timer),
+        }
+    }
+
+    fn restore_state(&mut self, state: &Dictionary) {
+        self.active = state_value(state, "active");
+        self.power = state_value(state, "power");
+        self.min_power = state_value(state, "min_power");
+        self.max_power = state_value(state, "max_power");
+        self.score = state_value(state, "score");
+        resume_timer(&mut self.auto_timer, state_value(state, "auto_timer"));
+        resume_timer(&mut self.manual_timer, state_value(state, "manual_timer"));
+        resume_timer(
+            &mut self.activation_timer,
+            state_value(state, "activation_timer"),
+        );
+
+        let power = self.power;
+        self.progress_bar.set_value(power);
+        self.signals().power_changed().emit(power);
+        self.show_power_on_pillar();
+        self.set_danger(1.0 - self.balance());
+    }
+
+    #[cfg(feature = "dev-tools")]
+    fn debug_state(&self) -> String {
+        let timer = |timer: &Gd<Timer>| {
+            if timer.is_stopped() {
+                "stopped".to_string()
+            } else {
+                format!("{:.2}s", timer.get_time_left())
+            }
+        };
+        format!(
+            "active={} power={:.1} auto={} manual={} danger={:.2}",
+            self.active,
+            self.power,
+            timer(&self.auto_timer),
+            timer(&self.manual_timer),
+            self.danger
+        )
+    }
+
+    fn closest_call(&self) -> GString {
+        format!("电量最低 {:.0}，最高 {:.0}", self.min_power, self.max_power).into()
+    }
+}
@@ -0,0 +1,296 @@
+/*!
+危险反馈：把零件的危险程度（0..1）映射到柱子的发光颜色/强度、脉动、标签颜色
+危险程度达到临界值时还会发出 `critical_entered`，可选地让屏幕抖动
+零件只需要告诉它是否点亮、当前的危险程度，以及按键的按下/松开
+开启减少动态效果后不再脉动、抖动和下沉，只保留颜色变化
+*/
+
+use crate::settings::Settings;
+use godot::classes::tween::{EaseType, TransitionType};
+use godot::classes::{INode, Label3D, MeshInstance3D, StandardMaterial3D, Tween};
+use godot::global::randf_range;
+use godot::prelude::*;
+use std::f64::consts::TAU;
+
+/// DangerFeedback drives the pillar and label of its part from the part's danger level
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct DangerFeedback {
+    base: Base<Node>,
+    #[export]
+    pillar: Option<Gd<MeshInstance3D>>,
+    #[export]
+    label: Option<Gd<Label3D>>,
+    /// Emission energy while the part is lit
+    #[export]
+    #[init(val = 10.0)]
+    lit_energy: f64,
+    /// Emission energy added at full danger
+    #[export]
+    #[init(val = 6.0)]
+    danger_energy: f64,
+    /// Danger from which the pillar starts to pulse
+    #[export]
+    #[init(val = 0.5)]
+    pulse_threshold: f64,
+    /// Scale change of the pulse at full danger
+    #[export]
+    #[init(val = 0.15)]
+    pulse_amount: f64,
+    /// Pulses per second
+    #[export]
+    #[init(val = 4.0)]
+    pulse_rate: f64,
+    /// Danger from which `critical_entered` fires and the screen may shake
+    #[export]
+    #[init(val = 0.8)]
+    critical_level: f64,
+    /// Whether `AudioCues` plays an alarm when this part turns critical
+    #[export]
+    #[init(val = true)]
+    pub audio_alert: bool,
+    /// Shake the current camera while this part is critical
+    #[export]
+    screen_shake: bool,
+    /// Camera offset of the shake at full danger
+    #[export]
+    #[init(val = 0.05)]
+    shake_strength: f64,
+    /// How far the pillar sinks while pressed
+    #[export]
+    #[init(val = 0.08)]
+    press_depth: f64,
+    /// Seconds the pillar takes to sink or come back up
+    #[export]
+    #[init(val = 0.08)]
+    press_duration: f64,
+    /// Emission energy of the flash on a press
+    #[export]
+    #[init(val = 8.0)]
+    flash_energy: f64,
+    /// Seconds the flash takes to fade
+    #[export]
+    #[init(val = 0.25)]
+    flash_duration: f64,
+
+    material: Option<Gd<StandardMaterial3D>>,
+    /// Emission color of the pillar when it is safe
+    base_emission: Color,
+    rest_scale: Vector3,
+    rest_position: Vector3,
+    label_color: Color,
+    danger_color: Color,
+    reduced_motion: bool,
+    danger: f64,
+    lit: bool,
+    critical: bool,
+    pulse_time: f64,
+    /// Remaining flash, 1.0 right after a press
+    flash: f64,
+    /// Fraction of the pillar height to show, for parts that display a level on it
+    fill: Option<f64>,
+    shown_fill: f64,
+    press_tween: Option<Gd<Tween>>,
+}
+
+#[godot_api]
+impl INode for DangerFeedback {
+    fn ready(&mut self) {
+        if let Some(pillar) = &mut self.pillar {
+            self.rest_scale = pillar.get_scale();
+            self.rest_position = pillar.get_position();
+            // 同一个场景的多个实例共用材质，复制一份再修改
+            let material = pillar
+                .get_material_override()
+                .and_then(|material| material.duplicate())
+                .and_then(|material| material.try_cast::<StandardMaterial3D>().ok());
+            if let Some(material) = &material {
+                self.base_emission = material.get_emission();
+                pillar.set_material_override(material);
+            }
+            self.material = material;
+        }
+        if let Some(label) = &self.label {
+            self.label_color = label.get_modulate();
+        }
+
+        Settings::singleton()
+            .signals()
+            .settings_changed()
+            .connect_other(self, Self::on_settings_changed);
+        self.on_settings_changed();
+    }
+
+    fn process(&mut self, delta: f64) {
+        let pulsing = !self.reduced_motion && self.danger >= self.pulse_threshold;
+        if let Some(fill) = self.fill {
+            self.shown_fill = if self.reduced_motion {
+                fill
+            } else {
+                self.shown_fill + (fill - self.shown_fill) * (delta * 10.0).min(1.0)
+            };
+        }
+        if let Some(pillar) = &mut self.pillar {
+            let mut scale = if pulsing {
+                self.pulse_time += delta;
+                let pulse = (self.pulse_time * TAU * self.pulse_rate).sin()
+                    * self.pulse_amount
+                    * self.danger;
+                self.rest_scale * (1.0 + pulse as f32)
+            } else {
+                self.pulse_time = 0.0;
+                self.rest_scale
+            };
+            if self.fill.is_some() {
+                // 留一点高度，空的时候也能看到柱子
+                scale.y *= (0.1 + 0.9 * self.shown_fill) as f32;
+            }
+            pillar.set_scale(scale);
+        }
+
+        if self.critical && self.screen_shake && !self.reduced_motion {
+            let strength = self.shake_strength * self.danger;
+            self.set_camera_offset(
+                randf_range(-strength, strength),
+                randf_range(-strength, strength),
+            );
+        }
+    }
+}
+
+#[godot_api]
+impl DangerFeedback {
+    /// The danger level reached `critical_level`
+    #[signal]
+    pub fn critical_entered();
+
+    /// Set the danger level of the part, 0..1
+    #[func]
+    pub fn set_danger(&mut self, level: f64) {
+        self.danger = level.clamp(0.0, 1.0);
+
+        let critical = self.danger >= self.critical_level;
+        if critical != self.critical {
+            self.critical = critical;
+            if critical {
+                self.signals().critical_entered().emit();
+            } else if self.screen_shake {
+                self.set_camera_offset(0.0, 0.0);
+            }
+        }
+
+        self.refresh();
+    }
+
+    /// Light the pillar up while the part needs the player's attention
+    #[func]
+    pub fn set_lit(&mut self, lit: bool) {
+        self.lit = lit;
+        self.refresh();
+    }
+
+    /// Sink the pillar and flash it, called when the part's key goes down
+    #[func]
+    pub fn press(&mut self) {
+        self.flash_glow();
+        if self.reduced_motion {
+            return;
+        }
+        let target = self.rest_position + Vector3::DOWN * self.press_depth as f32;
+        self.move_pillar(target);
+    }
+
+    /// Bring the pillar back up, called when the part's key goes up
+    #[func]
+    pub fn release(&mut self) {
+        let target = self.rest_position;
+        if self.reduced_motion {
+            if let Some(pillar) = &mut self.pillar {
+                pillar.set_position(target);
+            }
+            return;
+        }
+        self.move_pillar(target);
+    }
+
+    /// Show a level on the pillar height, 0..1
+    #[func]
+    pub fn set_fill(&mut self, fraction: f64) {
+        let fraction = fraction.clamp(0.0, 1.0);
+        if self.fill.is_none() {
+            self.shown_fill = fraction;
+        }
+        self.fill = Some(fraction);
+    }
+
+    #[func]
+    fn set_flash(&mut self, flash: f64) {
+        self.flash = flash;
+        self.refresh();
+    }
+
+    fn flash_glow(&mut self) {
+        let set_flash = self.to_gd().callable("set_flash");
+        let duration = self.flash_duration;
+        let Some(mut tween) = self.base_mut().create_tween() else {
+            return;
+        };
+        tween.tween_method(&set_flash, &1.0.to_variant(), &0.0.to_variant(), duration);
+    }
+
+    fn move_pillar(&mut self, target: Vector3) {
+        if let Some(mut tween) = self.press_tween.take() {
+            tween.kill();
+        }
+        let Some(pillar) = self.pillar.clone() else {
+            return;
+        };
+
+        let duration = self.press_duration;
+        let Some(mut tween) = self.base_mut().create_tween() else {
+            return;
+        };
+        tween.set_trans(TransitionType::QUAD);
+        tween.set_ease(EaseType::OUT);
+        tween.tween_property(&pillar, "position", &target.to_variant(), duration);
+        self.press_tween = Some(tween);
+    }
+
+    fn on_settings_changed(&mut self) {
+        let (palette, reduced_motion) = {
+            let settings = Settings::singleton();
+            let settings = settings.bind();
+            (settings.colorblind_palette, settings.reduced_motion)
+        };
+        self.danger_color = palette.danger_color();
+        self.reduced_motion = reduced_motion;
+        self.refresh();
+    }
+
+    /// Push the current danger and lit state to the pillar material and the label
+    fn refresh(&mut self) {
+        let danger = self.danger;
+        if let Some(material) = &mut self.material {
+            let lit_energy = if self.lit { self.lit_energy } else { 0.0 };
+            let energy =
+                lit_energy + self.danger_energy * self.danger + self.flash_energy * self.flash;
+            material.set_emission(self.base_emission.lerp(self.danger_color, danger));
+            material.set_emission_energy_multiplier(energy as f32);
+        }
+        if let Some(label) = &mut self.label {
+            label.set_modulate(self.label_color.lerp(self.danger_color, danger));
+        }
+    }
+
+    fn set_camera_offset(&self, h_offset: f64, v_offset: f64) {
+        let Some(mut camera) = self
+            .base()
+            .get_viewport()
+            .and_then(|viewport| viewport.get_camera_3d())
+        else {
+            return;
+        };
+        camera.set_h_offset(h_offset as f32);
+        camera.set_v_offset(v_offset as f32);
+    }
+}
@@ -1,4 +1,138 @@
+mod blue;
+pub mod feedback;
+mod pink;
 mod red;
 mod yellow;
-mod pink;
-mod blue;
\ No newline at end of file
+
+pub use blue::BlueButton;
+pub use pink::PinkButton;
+pub use red::RedButton;
+pub use yellow::YellowButton;
+
+use crate::parts::feedback::DangerFeedback;
+use godot::classes::Timer;
+use godot::prelude::*;
+
+/// Part scenes by part id, the id is the number in the scene name
+pub const PART_SCENES: [(i32, &str); 4] = [
+    (1001, "res://parts/Button1001.tscn"),
+    (1002, "res://parts/Button1002.tscn"),
+    (1003, "res://parts/Button1003.tscn"),
+    (1005, "res://parts/Button1005.tscn"),
+];
+
+pub fn part_scene(id: i32) -> Option<&'static str> {
+    PART_SCENES
+        .iter()
+        .find(|(part_id, _)| *part_id == id)
+        .map(|(_, path)| *path)
+}
+
+/// Name of a part as the HUD shows it, read from an instance of its scene that is never added to the tree
+pub fn part_title(id: i32) -> Option<GString> {
+    let scene = try_load::<PackedScene>(part_scene(id)?).ok()?;
+    let node = scene.try_instantiate_as::<Node>()?;
+    let title = node
+        .clone()
+        .try_dynify::<dyn Part>()
+        .ok()
+        .map(|part| part.dyn_bind().title());
+    node.free();
+    title
+}
+
+/// An exported property of a part that may be tuned at runtime
+pub struct Tunable {
+    pub property: &'static str,
+    pub label: &'static str,
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+}
+
+impl Tunable {
+    pub const fn new(
+        property: &'static str,
+        label: &'static str,
+        min: f64,
+        max: f64,
+        step: f64,
+    ) -> Self {
+        Self {
+            property,
+            label,
+            min,
+            max,
+            step,
+        }
+    }
+}
+
+/// Common interface every part exposes to `Game`, reached through `DynGd<_, dyn Part>`
+pub trait Part {
+    /// Score accumulated by this part during the current run
+    fn score(&self) -> f64;
+
+    /// Name shown to the player in the HUD
+    fn title(&self) -> GString;
+
+    /// The closest this part came to failing during the run, shown on the result screens
+    fn closest_call(&self) -> GString;
+
+    /// Parameters the practice sandbox exposes as sliders
+    fn tunables(&self) -> &'static [Tunable];
+
+    /// Runtime state of the run in progress, enough for `restore_state` to continue it
+    fn save_state(&self) -> Dictionary;
+
+    /// Continue from a state taken with `save_state`, called after the part was started
+    fn restore_state(&mut self, state: &Dictionary);
+
+    /// Internal state shown by the debug overlay every frame
+    #[cfg(feature = "dev-tools")]
+    fn debug_state(&self) -> String;
+}
+
+/// Read a value saved by `Part::save_state`, the default if it is missing
+pub fn state_value<T: FromGodot + Default>(state: &Dictionary, key: &str) -> T {
+    state
+        .get(key)
+        .and_then(|value| value.try_to().ok())
+        .unwrap_or_default()
+}
+
+/// Round `level` to hundredths and show it on `feedback`, returns the new level when it differs
+/// from `danger` so the part emits `danger_changed` only on a change
+pub fn update_danger(
+    danger: &mut f64,
+    feedback: &mut Gd<DangerFeedback>,
+    level: f64,
+) -> Option<f64> {
+    let level = (level.clamp(0.0, 1.0) * 100.0).round() / 100.0;
+    if level == *danger {
+        return None;
+    }
+    *danger = level;
+    feedback.bind_mut().set_danger(level);
+    Some(level)
+}
+
+/// Seconds left on a timer, negative when the timer is stopped
+pub fn timer_time_left(timer: &Gd<Timer>) -> f64 {
+    if timer.is_stopped() {
+        -1.0
+    } else {
+        timer.get_time_left()
+    }
+}
+
+/// Restart a timer with the time left saved by `timer_time_left`, later cycles keep the wait time
+pub fn resume_timer(timer: &mut Gd<Timer>, time_left: f64) {
+    timer.stop();
+    if time_left <= 0.0 {
+        return;
+    }
+    let wait_time = timer.get_wait_time();
+    timer.start_ex().time_sec(time_left).done();
+    timer.set_wait_time(wait_time);
+}
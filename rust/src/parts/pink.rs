@@ -1,9 +1,11 @@
 use crate::game_signals::GameSignals;
-use godot::classes::{
-    Engine, GDScript, INode3D, Input, InputEvent, MeshInstance3D, Node3D, StandardMaterial3D, Time,
-    Timer,
-};
+use crate::parts::feedback::DangerFeedback;
+use crate::parts::{Part, Tunable, resume_timer, state_value, timer_time_left, update_danger};
+use crate::settings::Settings;
+use godot::classes::tween::{EaseType, TransitionType};
+use godot::classes::{Engine, INode3D, Input, InputEvent, MeshInstance3D, Node3D, Timer, Tween};
 use godot::prelude::*;
+use log::debug;
 use std::f64::consts::PI;
 use std::ops::Range;
 
@@ -14,18 +16,50 @@ pub struct PinkButton {
     base: Base<Node3D>,
     #[init(node = "%Pillar")]
     pillar: OnReady<Gd<MeshInstance3D>>,
+    #[init(node = "%DangerFeedback")]
+    feedback: OnReady<Gd<DangerFeedback>>,
     #[init(val = Timer::new_alloc())]
     timer: Gd<Timer>,
     #[init(val = 0.5..3.0)]
     time_scale_range: Range<f64>,
+    /// Time scale taken off by one press
+    #[export]
     #[init(val = 0.1)]
     scale_delta: f64,
+    /// Time scale added every second while the part is active
+    #[export]
+    #[init(val = 0.1)]
+    drift_delta: f64,
+    /// 时间流速处于下限时每次结算获得的分数
+    #[export]
+    #[init(val = 10.0)]
+    score_rate: f64,
+    score: f64,
+    /// Highest time scale reached during the run
+    peak_time_scale: f64,
+    danger: f64,
+    /// Seconds after `game_started` before this part becomes active
+    #[export]
+    activation_delay: f64,
+    #[init(val = Timer::new_alloc())]
+    activation_timer: Gd<Timer>,
     active: bool, // Whether the part is currently active
+    rest_rotation: Vector3,
+    /// Rotation around z the gear is turning towards
+    gear_rotation: f32,
+    /// Seconds one gear step takes to turn
+    #[export]
+    #[init(val = 0.15)]
+    turn_duration: f64,
+    gear_tween: Option<Gd<Tween>>,
 }
 
 #[godot_api]
 impl INode3D for PinkButton {
     fn ready(&mut self) {
+        self.rest_rotation = self.base().get_rotation();
+        self.gear_rotation = self.rest_rotation.z;
+
         self.timer.set_wait_time(1.0);
         self.timer.set_one_shot(false);
         self.timer
@@ -35,23 +69,39 @@ impl INode3D for PinkButton {
         let timer = self.timer.clone();
         self.base_mut().add_child(&timer);
 
+        self.activation_timer.set_one_shot(true);
+        self.activation_timer
+            .signals()
+            .timeout()
+            .connect_other(self, Self::start);
+        let timer = self.activation_timer.clone();
+        self.base_mut().add_child(&timer);
+
+        GameSignals::singleton()
+            .signals()
+            .countdown_started()
+            .connect_other(self, Self::show_idle);
         GameSignals::singleton()
             .signals()
             .game_started()
-            .connect_other(self, Self::start);
+            .connect_other(self, Self::on_game_started);
         GameSignals::singleton()
             .signals()
             .game_stopped()
             .connect_other(self, Self::stop);
     }
 
-    fn input(&mut self, event: Gd<InputEvent>) {
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        if event.is_action_released("pink_button") {
+            self.feedback.bind_mut().release();
+        }
         if !self.active {
             return;
         }
 
         // 检查特定键是否被按下
         if Input::singleton().is_action_just_pressed("pink_button") {
+            self.feedback.bind_mut().press();
             if Engine::singleton().get_time_scale() <= self.time_scale_range.start {
                 return;
             }
@@ -61,54 +111,216 @@ impl INode3D for PinkButton {
     }
 }
 
+#[godot_api]
 impl PinkButton {
+    /// Engine time scale after this part changed it
+    #[signal]
+    pub fn time_scale_changed(scale: f64);
+
+    /// The gear turned one step, clockwise when the time scale went up
+    #[signal]
+    pub fn gear_turned(clockwise: bool);
+
+    /// How close the time scale is to the top of `time_scale_range`, 0..1
+    #[signal]
+    pub fn danger_changed(level: f64);
+
     fn on_timeout(&mut self) {
         if !self.active {
             return;
         }
 
+        self.score += self.score_rate * self.calmness();
+
         if Engine::singleton().get_time_scale() >= self.time_scale_range.end {
             return;
         }
 
-        godot_print!("pink button timeout");
+        debug!("pink button timeout");
         self.increase_global_time_scale();
         self.rotate_clockwise();
     }
 
+    /// Idle visuals shown during the countdown
+    fn show_idle(&mut self) {
+        if let Some(mut tween) = self.gear_tween.take() {
+            tween.kill();
+        }
+        let rotation = self.rest_rotation;
+        self.gear_rotation = rotation.z;
+        self.base_mut().set_rotation(rotation);
+    }
+
+    fn on_game_started(&mut self) {
+        if self.activation_delay > 0.0 {
+            self.activation_timer.set_wait_time(self.activation_delay);
+            self.activation_timer.start();
+        } else {
+            self.start();
+        }
+    }
+
     fn start(&mut self) {
-        godot_print!("pink button started");
+        debug!("pink button started");
         self.active = true;
+        self.score = 0.0;
+        self.peak_time_scale = 1.0;
         self.timer.start();
 
-        Engine::singleton().set_time_scale(1.0);
+        self.set_time_scale(1.0);
     }
 
     fn stop(&mut self) {
-        godot_print!("pink button stopped");
+        debug!("pink button stopped");
         self.active = false;
+        self.activation_timer.stop();
         self.timer.stop();
 
-        Engine::singleton().set_time_scale(1.0);
+        self.set_time_scale(1.0);
+        self.set_danger(0.0);
     }
 
     fn increase_global_time_scale(&mut self) {
-        let cur_scale = Engine::singleton().get_time_scale() + self.scale_delta;
-        Engine::singleton().set_time_scale(cur_scale);
+        let cur_scale = Engine::singleton().get_time_scale() + self.drift_delta;
+        self.set_time_scale(cur_scale);
     }
 
     fn decrease_global_time_scale(&mut self) {
         let cur_scale = Engine::singleton().get_time_scale() - self.scale_delta;
-        Engine::singleton().set_time_scale(cur_scale);
+        self.set_time_scale(cur_scale);
+    }
+
+    fn set_time_scale(&mut self, scale: f64) {
+        Engine::singleton().set_time_scale(scale);
+        if self.active {
+            self.peak_time_scale = self.peak_time_scale.max(scale);
+        }
+        self.signals().time_scale_changed().emit(scale);
+        self.set_danger(1.0 - self.calmness());
     }
 
     fn rotate_clockwise(&mut self) {
-        let angle = (PI * self.scale_delta) as f32;
-        self.base_mut().rotate_z(angle);
+        let angle = (PI * self.drift_delta) as f32;
+        self.turn_gear(angle);
+        self.signals().gear_turned().emit(true);
     }
 
     fn rotate_counterclockwise(&mut self) {
         let angle = (PI * self.scale_delta) as f32;
-        self.base_mut().rotate_z(-angle);
+        self.turn_gear(-angle);
+        self.signals().gear_turned().emit(false);
+    }
+
+    /// Turn the gear by `angle`, smoothly unless reduced motion is on
+    fn turn_gear(&mut self, angle: f32) {
+        self.gear_rotation += angle;
+        if let Some(mut tween) = self.gear_tween.take() {
+            tween.kill();
+        }
+
+        let target = self.gear_rotation;
+        if Settings::singleton().bind().reduced_motion {
+            let mut rotation = self.base().get_rotation();
+            rotation.z = target;
+            self.base_mut().set_rotation(rotation);
+            return;
+        }
+
+        let this = self.to_gd();
+        let duration = self.turn_duration;
+        let Some(mut tween) = self.base_mut().create_tween() else {
+            return;
+        };
+        tween.set_trans(TransitionType::SINE);
+        tween.set_ease(EaseType::OUT);
+        tween.tween_property(&this, "rotation:z", &target.to_variant(), duration);
+        self.gear_tween = Some(tween);
+    }
+
+    /// 1.0 at the bottom of `time_scale_range`, 0.0 at the top
+    fn calmness(&self) -> f64 {
+        let range = self.time_scale_range.end - self.time_scale_range.start;
+        let scale = Engine::singleton().get_time_scale();
+        ((self.time_scale_range.end - scale) / range).clamp(0.0, 1.0)
+    }
+
+    fn set_danger(&mut self, level: f64) {
+        if let Some(level) = update_danger(&mut self.danger, &mut self.feedback, level) {
+            self.signals().danger_changed().emit(level);
+        }
+    }
+}
+
+/// Properties the practice panel can tune
+const TUNABLES: [Tunable; 3] = [
+    Tunable::new("scale_delta", "每次按下时间流速变化", 0.05, 0.5, 0.05),
+    Tunable::new("drift_delta", "每秒时间流速变化", 0.05, 0.5, 0.05),
+    Tunable::new("score_rate", "得分倍率", 1.0, 50.0, 1.0),
+];
+
+#[godot_dyn]
+impl Part for PinkButton {
+    fn score(&self) -> f64 {
+        self.score
+    }
+
+    fn title(&self) -> GString {
+        "粉色按钮".into()
+    }
+
+    fn tunables(&self) -> &'static [Tunable] {
+        &TUNABLES
+    }
+
+    fn save_state(&self) -> Dictionary {
+        vdict! {
+            "active": self.active,
+            "score": self.score,
+            "peak_time_scale": self.peak_time_scale,
+            "time_scale": Engine::singleton().get_time_scale(),
+            "gear_rotation": self.gear_rotation,
+            "timer": timer_time_left(&self.timer),
+            "activation_timer": timer_time_left(&self.activation_timer),
+        }
     }
-}
\ No newline at end of file
+
+    fn restore_state(&mut self, state: &Dictionary) {
+        self.active = state_value(state, "active");
+        self.score = state_value(state, "score");
+        self.peak_time_scale = state_value(state, "peak_time_scale");
+        resume_timer(&mut self.timer, state_value(state, "timer"));
+        resume_timer(
+            &mut self.activation_timer,
+            state_value(state, "activation_timer"),
+        );
+        self.set_time_scale(state_value(state, "time_scale"));
+
+        if let Some(mut tween) = self.gear_tween.take() {
+            tween.kill();
+        }
+        self.gear_rotation = state_value(state, "gear_rotation");
+        let mut rotation = self.base().get_rotation();
+        rotation.z = self.gear_rotation;
+        self.base_mut().set_rotation(rotation);
+    }
+
+    #[cfg(feature = "dev-tools")]
+    fn debug_state(&self) -> String {
+        let timer = if self.timer.is_stopped() {
+            "stopped".to_string()
+        } else {
+            format!("{:.2}s", self.timer.get_time_left())
+        };
+        format!(
+            "active={} time_scale={:.2} timer={} danger={:.2}",
+            self.active,
+            Engine::singleton().get_time_scale(),
+            timer,
+            self.danger
+        )
+    }
+
+    fn closest_call(&self) -> GString {
+        format!("时间流速最高 {:.1} 倍", self.peak_time_scale).into()
+    }
+}
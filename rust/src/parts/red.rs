@@ -1,11 +1,11 @@
 use crate::game_signals::GameSignals;
+use crate::parts::feedback::DangerFeedback;
+use crate::parts::{Part, Tunable, resume_timer, state_value, timer_time_left, update_danger};
 use godot::classes::notify::Node3DNotification;
-use godot::classes::{
-    Engine, INode3D, InputEvent, InputEventKey, MeshInstance3D, Node3D, StandardMaterial3D, Timer,
-};
+use godot::classes::{INode3D, InputEvent, InputEventKey, MeshInstance3D, Node3D, Timer};
 use godot::global::Key;
 use godot::prelude::*;
-use godot_tokio::AsyncRuntime;
+use log::debug;
 
 /// ButtonPart is a game part that requires the player to press a specific key within a time limit
 #[derive(GodotClass)]
@@ -14,27 +14,44 @@ pub struct RedButton {
     base: Base<Node3D>,
     #[init(node = "%Pillar")]
     pillar: OnReady<Gd<MeshInstance3D>>,
+    #[init(node = "%DangerFeedback")]
+    feedback: OnReady<Gd<DangerFeedback>>,
     timer: Option<Gd<Timer>>,
-    #[init(val = Key::SPACE)]
-    required_key: Key, // Key code that player needs to press
+    #[export]
+    #[var(get, set = set_time_limit)]
     #[init(val = 1.0)]
     time_limit: f64, // Time limit in seconds
+    /// Points for a press at the very start of the window
+    #[export]
+    #[init(val = 10.0)]
+    score_rate: f64,
+    /// Seconds after `game_started` before this part becomes active
+    #[export]
+    activation_delay: f64,
+    #[init(val = Timer::new_alloc())]
+    activation_timer: Gd<Timer>,
     active: bool, // Whether the part is currently active
+    /// Key set through `set_required_key`, used instead of the `red_button` action
+    required_key: Option<Key>,
 
     pressed: bool,
+    window_open: bool,
+    /// Seconds from a window opening to the press, fastest of the run
+    fastest_press: Option<f64>,
+    /// Least window left at a press, 1.0 until the first press
+    closest_press: f64,
+    score: f64,
+    danger: f64,
 }
 
 #[godot_api]
 impl INode3D for RedButton {
     fn on_notification(&mut self, what: Node3DNotification) {
-        match what {
-            Node3DNotification::PREDELETE => {
-                if let Some(timer) = &self.timer {
-                    timer.clone().free();
-                    self.timer = None;
-                }
+        if what == Node3DNotification::PREDELETE {
+            if let Some(timer) = &self.timer {
+                timer.clone().free();
+                self.timer = None;
             }
-            _ => {}
         }
     }
 
@@ -50,59 +67,119 @@ impl INode3D for RedButton {
         self.base_mut().add_child(&timer);
         self.timer = Some(timer);
 
+        self.activation_timer.set_one_shot(true);
+        self.activation_timer
+            .signals()
+            .timeout()
+            .connect_other(self, Self::start);
+        let timer = self.activation_timer.clone();
+        self.base_mut().add_child(&timer);
+
+        GameSignals::singleton()
+            .signals()
+            .countdown_started()
+            .connect_other(self, Self::show_idle);
         GameSignals::singleton()
             .signals()
             .game_started()
-            .connect_other(self, Self::start);
+            .connect_other(self, Self::on_game_started);
         GameSignals::singleton()
             .signals()
             .game_stopped()
             .connect_other(self, Self::stop);
     }
 
-    fn input(&mut self, event: Gd<InputEvent>) {
+    fn process(&mut self, _delta: f64) {
         if !self.active {
             return;
         }
 
-        // Check if the event is a key press
-        if let Ok(key_event) = event.try_cast::<InputEventKey>() {
-            if key_event.is_pressed()
-                && !key_event.is_echo()
-                && key_event.get_keycode() == self.required_key
-            {
-                godot_print!("Correct key pressed!");
-                self.pressed = true;
+        let danger = if self.pressed {
+            0.0
+        } else {
+            1.0 - self.window_left()
+        };
+        self.set_danger(danger);
+    }
+
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        if self.is_required_key(&event, false) {
+            self.feedback.bind_mut().release();
+        }
+        if !self.active {
+            return;
+        }
+
+        // Check if the bound key was pressed, rebinding happens through the input map
+        if self.is_required_key(&event, true) {
+            debug!("Correct key pressed!");
+            self.feedback.bind_mut().press();
+            if !self.pressed {
+                let window_left = self.window_left();
+                self.score += self.score_rate * window_left;
+                let elapsed = self.time_limit * (1.0 - window_left);
+                self.fastest_press = Some(
+                    self.fastest_press
+                        .map_or(elapsed, |fastest| fastest.min(elapsed)),
+                );
+                self.closest_press = self.closest_press.min(window_left);
             }
+            self.pressed = true;
         }
     }
 }
 
 #[godot_api]
 impl RedButton {
+    /// How much of the press window has passed without a press, 0..1
+    #[signal]
+    pub fn danger_changed(level: f64);
+
+    /// A new press window started
+    #[signal]
+    pub fn window_opened();
+
+    /// The current press window ended, `pressed` tells whether it was pressed in time
+    #[signal]
+    pub fn window_closed(pressed: bool);
+
+    /// Idle visuals shown during the countdown
+    fn show_idle(&mut self) {
+        self.feedback.bind_mut().set_lit(false);
+    }
+
+    fn on_game_started(&mut self) {
+        if self.activation_delay > 0.0 {
+            self.activation_timer.set_wait_time(self.activation_delay);
+            self.activation_timer.start();
+        } else {
+            self.start();
+        }
+    }
+
     /// Start the button part
     #[func]
     pub fn start(&mut self) {
-        godot_print!("Button part started");
+        debug!("Button part started");
         self.active = true;
+        self.pressed = false;
+        self.score = 0.0;
+        self.fastest_press = None;
+        self.closest_press = 1.0;
         self.reset_timer();
-        self.pillar
-            .get_material_override()
-            .unwrap()
-            .cast::<StandardMaterial3D>()
-            .set_emission_energy_multiplier(10.0);
+        self.open_window();
+        self.feedback.bind_mut().set_lit(true);
     }
     /// Stop the button part
     #[func]
     pub fn stop(&mut self) {
-        godot_print!("Button part stopped");
+        debug!("Button part stopped");
         self.active = false;
+        self.activation_timer.stop();
         self.stop_timer();
-        self.pillar
-            .get_material_override()
-            .unwrap()
-            .cast::<StandardMaterial3D>()
-            .set_emission_energy_multiplier(0.0);
+        self.close_window();
+        self.set_danger(0.0);
+        self.feedback.bind_mut().set_lit(false);
     }
 
     /// Reset the timer
@@ -127,27 +204,70 @@ impl RedButton {
             return;
         }
 
+        self.close_window();
         if self.pressed {
             self.pressed = false;
+            self.open_window();
             return;
         }
 
         self.stop_timer();
-        godot::task::spawn(async move {
-            AsyncRuntime::spawn(async {
-                tokio::time::sleep(tokio::time::Duration::from_micros(1)).await;
-            })
-            .await
-            .unwrap();
-            godot_print!("Time's up! Player failed to press the key in time.");
-            GameSignals::singleton().signals().game_failure().emit();
-        });
-    }
-
-    /// Set the required key
+        debug!("Time's up! Player failed to press the key in time.");
+        GameSignals::singleton()
+            .bind_mut()
+            .emit_game_failure("红色按钮没有及时按下");
+    }
+
+    fn open_window(&mut self) {
+        self.window_open = true;
+        self.signals().window_opened().emit();
+    }
+
+    fn close_window(&mut self) {
+        if !self.window_open {
+            return;
+        }
+        self.window_open = false;
+        let pressed = self.pressed;
+        self.signals().window_closed().emit(pressed);
+    }
+
+    /// Fraction of the current press window that is still left, 1.0 right after it opens
+    fn window_left(&self) -> f64 {
+        match &self.timer {
+            Some(timer) if timer.get_wait_time() > 0.0 => {
+                (timer.get_time_left() / timer.get_wait_time()).clamp(0.0, 1.0)
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn set_danger(&mut self, level: f64) {
+        if let Some(level) = update_danger(&mut self.danger, &mut self.feedback, level) {
+            self.signals().danger_changed().emit(level);
+        }
+    }
+
+    /// Set the required key for this button only, kept for scenes and scripts from before the
+    /// `red_button` action; the persisted binding stays as it is
     #[func]
     pub fn set_required_key(&mut self, key: Key) {
-        self.required_key = key;
+        self.required_key = Some(key);
+    }
+
+    /// Whether the event presses or releases this button's key
+    fn is_required_key(&self, event: &Gd<InputEvent>, pressed: bool) -> bool {
+        match self.required_key {
+            // 单个按钮的覆盖键，不改全局的按键绑定
+            Some(key) => event
+                .clone()
+                .try_cast::<InputEventKey>()
+                .is_ok_and(|event| {
+                    event.get_keycode() == key && event.is_pressed() == pressed && !event.is_echo()
+                }),
+            None if pressed => event.is_action_pressed("red_button"),
+            None => event.is_action_released("red_button"),
+        }
     }
 
     /// Set the time limit
@@ -159,3 +279,80 @@ impl RedButton {
         }
     }
 }
+
+/// Properties the practice panel can tune
+const TUNABLES: [Tunable; 2] = [
+    Tunable::new("time_limit", "按下时限（秒）", 0.3, 3.0, 0.1),
+    Tunable::new("score_rate", "得分倍率", 1.0, 50.0, 1.0),
+];
+
+#[godot_dyn]
+impl Part for RedButton {
+    fn score(&self) -> f64 {
+        self.score
+    }
+
+    fn title(&self) -> GString {
+        "红色按钮".into()
+    }
+
+    fn tunables(&self) -> &'static [Tunable] {
+        &TUNABLES
+    }
+
+    fn save_state(&self) -> Dictionary {
+        vdict! {
+            "active": self.active,
+            "pressed": self.pressed,
+            "window_open": self.window_open,
+            "fastest_press": self.fastest_press.unwrap_or(-1.0),
+            "closest_press": self.closest_press,
+            "score": self.score,
+            "timer": self.timer.as_ref().map_or(-1.0, timer_time_left),
+            "activation_timer": timer_time_left(&self.activation_timer),
+        }
+    }
+
+    fn restore_state(&mut self, state: &Dictionary) {
+        self.active = state_value(state, "active");
+        self.pressed = state_value(state, "pressed");
+        self.window_open = state_value(state, "window_open");
+        let fastest_press: f64 = state_value(state, "fastest_press");
+        self.fastest_press = (fastest_press >= 0.0).then_some(fastest_press);
+        self.closest_press = state_value(state, "closest_press");
+        self.score = state_value(state, "score");
+        if let Some(timer) = &mut self.timer {
+            resume_timer(timer, state_value(state, "timer"));
+        }
+        resume_timer(
+            &mut self.activation_timer,
+            state_value(state, "activation_timer"),
+        );
+        let active = self.active;
+        self.feedback.bind_mut().set_lit(active);
+    }
+
+    #[cfg(feature = "dev-tools")]
+    fn debug_state(&self) -> String {
+        format!(
+            "active={} pressed={} window_open={} window_left={:.2}s danger={:.2}",
+            self.active,
+            self.pressed,
+            self.window_open,
+            self.time_limit * self.window_left(),
+            self.danger
+        )
+    }
+
+    fn closest_call(&self) -> GString {
+        match self.fastest_press {
+            Some(fastest) => format!(
+                "最快 {:.2} 秒按下，最慢时只剩 {:.2} 秒",
+                fastest,
+                self.time_limit * self.closest_press
+            )
+            .into(),
+            None => "没有按下过".into(),
+        }
+    }
+}
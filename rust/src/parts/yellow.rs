@@ -4,12 +4,12 @@
 */
 
 use crate::game_signals::GameSignals;
+use crate::parts::feedback::DangerFeedback;
+use crate::parts::{Part, Tunable, resume_timer, state_value, timer_time_left, update_danger};
 use godot::classes::notify::Node3DNotification;
-use godot::classes::{
-    INode3D, Input, InputEvent, MeshInstance3D, Node3D, StandardMaterial3D, Time, Timer,
-};
-use godot::global::{Key, randf_range};
+use godot::classes::{INode3D, Input, InputEvent, MeshInstance3D, Node3D, Time, Timer};
 use godot::prelude::*;
+use log::debug;
 
 /// ButtonPart is a game part that requires the player to press a specific key within a time limit
 #[derive(GodotClass)]
@@ -18,30 +18,47 @@ pub struct YellowButton {
     base: Base<Node3D>,
     #[init(node = "%Pillar")]
     pillar: OnReady<Gd<MeshInstance3D>>,
+    #[init(node = "%DangerFeedback")]
+    feedback: OnReady<Gd<DangerFeedback>>,
     #[init(val = Timer::new_alloc())]
     timer1: Gd<Timer>,
     #[init(val = Timer::new_alloc())]
     timer2: Gd<Timer>,
     #[init(val = (5.0, 10.0))]
     delay_time: (f64, f64), // Time limit in seconds
+    #[export]
+    #[var(get, set = set_time_limit)]
     #[init(val = 5.0)]
     time_limit: f64, // Time limit in seconds
+    #[export]
     #[init(val = 2.0)]
     press_time: f64, // Time limit in seconds
+    /// 亮灯后立刻按住时获得的分数
+    #[export]
+    #[init(val = 10.0)]
+    score_rate: f64,
+    /// Seconds after `game_started` before this part becomes active
+    #[export]
+    activation_delay: f64,
+    #[init(val = Timer::new_alloc())]
+    activation_timer: Gd<Timer>,
     active: bool, // Whether the part is currently active
 
     pressed: bool,
+    lit: bool,
     press_start_time: f64,
+    paused_at: f64,
+    /// Longest wait between the light turning on and the hold, in seconds
+    latest_hold: Option<f64>,
+    score: f64,
+    danger: f64,
 }
 
 #[godot_api]
 impl INode3D for YellowButton {
     fn on_notification(&mut self, what: Node3DNotification) {
-        match what {
-            Node3DNotification::PREDELETE => {
-                self.timer1.clone().free();
-            }
-            _ => {}
+        if what == Node3DNotification::PREDELETE {
+            self.timer1.clone().free();
         }
     }
 
@@ -64,17 +81,50 @@ impl INode3D for YellowButton {
         let timer = self.timer2.clone();
         self.base_mut().add_child(&timer);
 
+        self.activation_timer.set_one_shot(true);
+        self.activation_timer
+            .signals()
+            .timeout()
+            .connect_other(self, Self::start);
+        let timer = self.activation_timer.clone();
+        self.base_mut().add_child(&timer);
+
+        GameSignals::singleton()
+            .signals()
+            .countdown_started()
+            .connect_other(self, Self::show_idle);
         GameSignals::singleton()
             .signals()
             .game_started()
-            .connect_other(self, Self::start);
+            .connect_other(self, Self::on_game_started);
         GameSignals::singleton()
             .signals()
             .game_stopped()
             .connect_other(self, Self::stop);
+        GameSignals::singleton()
+            .signals()
+            .game_paused()
+            .connect_other(self, Self::on_game_paused);
+        GameSignals::singleton()
+            .signals()
+            .game_resumed()
+            .connect_other(self, Self::on_game_resumed);
+    }
+
+    fn process(&mut self, _delta: f64) {
+        // 亮灯后还没按住时，危险程度随剩余时间增加
+        let danger = if self.active && !self.pressed {
+            1.0 - self.timer2.get_time_left() / self.time_limit
+        } else {
+            0.0
+        };
+        self.set_danger(danger);
     }
 
-    fn input(&mut self, event: Gd<InputEvent>) {
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        if event.is_action_released("yellow_button") {
+            self.feedback.bind_mut().release();
+        }
         if !self.active {
             return;
         }
@@ -84,7 +134,14 @@ impl INode3D for YellowButton {
             // 如果键刚开始被按下，记录开始时间
             if !self.pressed {
                 self.pressed = true;
+                self.feedback.bind_mut().press();
                 self.press_start_time = Time::singleton().get_unix_time_from_system();
+                // 越早按住得分越高
+                let promptness = self.timer2.get_time_left() / self.time_limit;
+                let waited = self.time_limit - self.timer2.get_time_left();
+                self.latest_hold =
+                    Some(self.latest_hold.map_or(waited, |latest| latest.max(waited)));
+                self.score += self.score_rate * promptness.clamp(0.0, 1.0);
                 return;
             }
 
@@ -94,16 +151,18 @@ impl INode3D for YellowButton {
 
             // 检查是否达到所需持续时间
             if current_duration >= self.press_time {
-                godot_print!("按键已持续按下2秒钟！");
-                // 执行你的操作...
+                debug!("按键已持续按下2秒钟！");
+                self.signals().hold_completed().emit();
                 // 重置状态（如果你只想触发一次）
-                self.start();
+                self.restart_cycle();
             }
         } else {
             // 键被释放，重置状态
             if self.pressed {
                 self.stop();
-                GameSignals::singleton().bind_mut().emit_game_failure();
+                GameSignals::singleton()
+                    .bind_mut()
+                    .emit_game_failure("黄色按钮松开得太早了");
             }
         }
     }
@@ -111,38 +170,98 @@ impl INode3D for YellowButton {
 
 #[godot_api]
 impl YellowButton {
+    /// How much of the hold window has passed without a hold, 0..1
+    #[signal]
+    pub fn danger_changed(level: f64);
+
+    /// The light turned on, the player has `time_limit` seconds to start holding
+    #[signal]
+    pub fn window_opened();
+
+    /// The light turned off
+    #[signal]
+    pub fn window_closed();
+
+    /// The key was held for `press_time`, a new cycle starts
+    #[signal]
+    pub fn hold_completed();
+
+    /// Idle visuals shown during the countdown
+    fn show_idle(&mut self) {
+        self.light_off();
+    }
+
+    fn on_game_started(&mut self) {
+        if self.activation_delay > 0.0 {
+            self.activation_timer.set_wait_time(self.activation_delay);
+            self.activation_timer.start();
+        } else {
+            self.start();
+        }
+    }
+
     pub fn start(&mut self) {
-        godot_print!("yellow button started");
+        self.score = 0.0;
+        self.latest_hold = None;
+        self.restart_cycle();
+    }
+
+    fn restart_cycle(&mut self) {
+        debug!("yellow button started");
         // 延时5~10秒
         self.active = false;
         self.pressed = false;
         self.press_start_time = 0.0;
-        self.timer1.set_wait_time(randf_range(5.0, 10.0));
+        let delay = GameSignals::singleton()
+            .bind()
+            .rng
+            .clone()
+            .randf_range(self.delay_time.0 as f32, self.delay_time.1 as f32);
+        self.timer1.set_wait_time(delay as f64);
         self.reset_timer();
         self.light_off();
     }
 
     pub fn stop(&mut self) {
-        godot_print!("yellow button stopped");
+        debug!("yellow button stopped");
         self.active = false;
         self.pressed = false;
+        self.activation_timer.stop();
         self.stop_timer();
         self.light_off();
     }
     pub fn reset_timer(&mut self) {
-        godot_print!("yellow button reset timer");
+        debug!("yellow button reset timer");
         self.timer1.start();
         self.timer2.stop();
     }
 
+    /// Seconds the player has to start holding after the light turns on
+    #[func]
+    pub fn set_time_limit(&mut self, time: f64) {
+        self.time_limit = time;
+        self.timer2.set_wait_time(time);
+    }
+
+    fn on_game_paused(&mut self) {
+        self.paused_at = Time::singleton().get_unix_time_from_system();
+    }
+
+    /// 按住时长按真实时间计算，暂停的时间不算在内
+    fn on_game_resumed(&mut self) {
+        if self.pressed {
+            self.press_start_time += Time::singleton().get_unix_time_from_system() - self.paused_at;
+        }
+    }
+
     fn stop_timer(&mut self) {
-        godot_print!("yellow button stop timer");
+        debug!("yellow button stop timer");
         self.timer1.stop();
         self.timer2.stop();
     }
 
     fn start_check_press(&mut self) {
-        godot_print!("yellow button start check press");
+        debug!("yellow button start check press");
         self.active = true;
         // 5秒内单次按下满足2秒则重新启动，否则失败
         self.light_on();
@@ -159,25 +278,119 @@ impl YellowButton {
             return;
         }
 
-        godot_print!("yellow button timeout");
-        GameSignals::singleton().bind_mut().emit_game_failure();
+        debug!("yellow button timeout");
+        GameSignals::singleton()
+            .bind_mut()
+            .emit_game_failure("黄色按钮没有及时按住");
     }
 
     fn light_on(&mut self) {
-        godot_print!("yellow button light on");
-        self.pillar
-            .get_material_override()
-            .unwrap()
-            .cast::<StandardMaterial3D>()
-            .set_emission_energy_multiplier(10.0);
+        debug!("yellow button light on");
+        self.feedback.bind_mut().set_lit(true);
+        self.lit = true;
+        self.signals().window_opened().emit();
     }
 
     fn light_off(&mut self) {
-        godot_print!("yellow button light off");
-        self.pillar
-            .get_material_override()
-            .unwrap()
-            .cast::<StandardMaterial3D>()
-            .set_emission_energy_multiplier(0.0);
-    }
-}
\ No newline at end of file
+        debug!("yellow button light off");
+        self.feedback.bind_mut().set_lit(false);
+        if self.lit {
+            self.lit = false;
+            self.signals().window_closed().emit();
+        }
+    }
+
+    fn set_danger(&mut self, level: f64) {
+        if let Some(level) = update_danger(&mut self.danger, &mut self.feedback, level) {
+            self.signals().danger_changed().emit(level);
+        }
+    }
+}
+
+/// Properties the practice panel can tune
+const TUNABLES: [Tunable; 3] = [
+    Tunable::new("time_limit", "按住时限（秒）", 1.0, 10.0, 0.5),
+    Tunable::new("press_time", "需要按住（秒）", 0.5, 5.0, 0.5),
+    Tunable::new("score_rate", "得分倍率", 1.0, 50.0, 1.0),
+];
+
+#[godot_dyn]
+impl Part for YellowButton {
+    fn score(&self) -> f64 {
+        self.score
+    }
+
+    fn title(&self) -> GString {
+        "黄色按钮".into()
+    }
+
+    fn tunables(&self) -> &'static [Tunable] {
+        &TUNABLES
+    }
+
+    fn save_state(&self) -> Dictionary {
+        // 按住的时长按真实时间保存
+        let held = if self.pressed {
+            Time::singleton().get_unix_time_from_system() - self.press_start_time
+        } else {
+            0.0
+        };
+        vdict! {
+            "active": self.active,
+            "pressed": self.pressed,
+            "lit": self.lit,
+            "held": held,
+            "latest_hold": self.latest_hold.unwrap_or(-1.0),
+            "score": self.score,
+            "timer1": timer_time_left(&self.timer1),
+            "timer2": timer_time_left(&self.timer2),
+            "activation_timer": timer_time_left(&self.activation_timer),
+        }
+    }
+
+    fn restore_state(&mut self, state: &Dictionary) {
+        self.active = state_value(state, "active");
+        self.pressed = state_value(state, "pressed");
+        self.lit = state_value(state, "lit");
+        if self.pressed {
+            let held: f64 = state_value(state, "held");
+            self.press_start_time = Time::singleton().get_unix_time_from_system() - held;
+        }
+        let latest_hold: f64 = state_value(state, "latest_hold");
+        self.latest_hold = (latest_hold >= 0.0).then_some(latest_hold);
+        self.score = state_value(state, "score");
+        resume_timer(&mut self.timer1, state_value(state, "timer1"));
+        resume_timer(&mut self.timer2, state_value(state, "timer2"));
+        resume_timer(
+            &mut self.activation_timer,
+            state_value(state, "activation_timer"),
+        );
+        let lit = self.lit;
+        self.feedback.bind_mut().set_lit(lit);
+    }
+
+    #[cfg(feature = "dev-tools")]
+    fn debug_state(&self) -> String {
+        let phase = if self.pressed {
+            let held = Time::singleton().get_unix_time_from_system() - self.press_start_time;
+            format!(
+                "holding {:.0}%",
+                (held / self.press_time * 100.0).clamp(0.0, 100.0)
+            )
+        } else if self.lit {
+            format!("lit {:.2}s left", self.timer2.get_time_left())
+        } else if !self.timer1.is_stopped() {
+            format!("waiting {:.2}s", self.timer1.get_time_left())
+        } else {
+            "idle".to_string()
+        };
+        format!("phase={} danger={:.2}", phase, self.danger)
+    }
+
+    fn closest_call(&self) -> GString {
+        match self.latest_hold {
+            Some(latest) => format!("最晚在亮灯 {:.2} 秒后按住", latest).into(),
+            None => "没有按住过".into(),
+        }
+    }
+}
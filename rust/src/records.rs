@@ -0,0 +1,270 @@
+/*!
+每局结束后的记录，保存在 `user://runs.json`，并据此生成每个关卡/模式的排行榜
+文件带版本号，旧版本会被迁移，损坏的文件会被备份后重新开始
+新版本写的文件只读取、不写回，免得降级后覆盖新数据
+*/
+
+use crate::game_controller::{Difficulty, GameMode};
+use crate::replay::Replay;
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{DirAccess, FileAccess, Json};
+use godot::prelude::*;
+use log::{error, warn};
+
+#[derive(GodotConvert, Var, Export, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[godot(via = GString)]
+pub enum RunOutcome {
+    Victory,
+    Failure,
+    /// The run was stopped without a victory or a failure
+    #[default]
+    Aborted,
+}
+
+/// One finished run
+#[derive(Clone, Debug, Default)]
+pub struct RunRecord {
+    pub mode: GameMode,
+    pub level: i32,
+    pub difficulty: Difficulty,
+    pub seed: i64,
+    pub score: f64,
+    /// Game time spent in the run, in seconds
+    pub duration: f64,
+    pub outcome: RunOutcome,
+    pub failure_reason: GString,
+    /// Unix time the run finished at
+    pub finished_at: f64,
+}
+
+impl RunRecord {
+    fn to_dictionary(&self) -> Dictionary {
+        vdict! {
+            "mode": self.mode,
+            "level": self.level,
+            "difficulty": self.difficulty,
+            // JSON numbers are doubles, keep the seed exact
+            "seed": self.seed.to_string(),
+            "score": self.score,
+            "duration": self.duration,
+            "outcome": self.outcome,
+            "failure_reason": self.failure_reason.clone(),
+            "finished_at": self.finished_at,
+        }
+    }
+
+    fn from_dictionary(dict: &Dictionary) -> Option<Self> {
+        let number = |key: &str| dict.get(key)?.try_to::<f64>().ok();
+
+        Some(Self {
+            mode: dict.get("mode")?.try_to().ok()?,
+            level: number("level")? as i32,
+            difficulty: dict.get("difficulty")?.try_to().ok()?,
+            seed: dict
+                .get("seed")?
+                .try_to::<GString>()
+                .ok()?
+                .to_string()
+                .parse()
+                .ok()?,
+            score: number("score")?,
+            duration: number("duration")?,
+            outcome: dict.get("outcome")?.try_to().ok()?,
+            failure_reason: dict
+                .get("failure_reason")
+                .and_then(|reason| reason.try_to().ok())
+                .unwrap_or_default(),
+            finished_at: number("finished_at").unwrap_or_default(),
+        })
+    }
+}
+
+/// Everything the result screens show about the run that just finished
+#[derive(Clone, Debug, Default)]
+pub struct RunSummary {
+    pub record: RunRecord,
+    /// Part title and its closest call, in the order of the `Parts` children
+    pub closest_calls: Vec<(GString, GString)>,
+    pub replay: Replay,
+}
+
+impl RunSummary {
+    /// Multi-line stats for the result screens
+    pub fn describe(&self) -> String {
+        let record = &self.record;
+        let mut text = format!(
+            "用时 {:.1} 秒\n得分 {:.0}\n种子 {}\n",
+            record.duration, record.score, record.seed
+        );
+        if !record.failure_reason.is_empty() {
+            text += &format!("失败原因：{}\n", record.failure_reason);
+        }
+        for (title, closest_call) in &self.closest_calls {
+            text += &format!("{}：{}\n", title, closest_call);
+        }
+        text
+    }
+}
+
+/// All finished runs, oldest first
+#[derive(Default)]
+pub struct RunHistory {
+    pub runs: Vec<RunRecord>,
+    /// The file was written by a newer version, saving would lose its data
+    read_only: bool,
+}
+
+impl RunHistory {
+    pub const PATH: &'static str = "user://runs.json";
+    /// Bump together with a new step in [`RunHistory::migrate`]
+    pub const VERSION: i64 = 1;
+    /// Number of runs kept on disk
+    const MAX_RUNS: usize = 200;
+
+    /// Load the history, never failing: a missing file is an empty history, a
+    /// corrupt one is reported, moved aside and replaced by an empty history, and
+    /// one from a newer version is read as far as possible but never saved over
+    pub fn load() -> Self {
+        if !FileAccess::file_exists(Self::PATH) {
+            return Self::default();
+        }
+
+        let text = FileAccess::get_file_as_string(Self::PATH);
+        let mut json = Json::new_gd();
+        if json.parse(&text) != godot::global::Error::OK {
+            error!(
+                "run history is corrupt (line {}: {})",
+                json.get_error_line(),
+                json.get_error_message()
+            );
+            Self::back_up_corrupt();
+            return Self::default();
+        }
+
+        match Self::migrate(json.get_data()) {
+            Ok(history) => history,
+            Err(message) => {
+                error!("run history can not be read: {}", message);
+                Self::back_up_corrupt();
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if self.read_only {
+            error!("run history was written by a newer version, not saving");
+            return;
+        }
+        let runs = self
+            .runs
+            .iter()
+            .map(|run| run.to_dictionary().to_variant())
+            .collect::<VariantArray>();
+        let data = vdict! {
+            "version": Self::VERSION,
+            "runs": runs,
+        };
+
+        match FileAccess::open(Self::PATH, ModeFlags::WRITE) {
+            Some(mut file) => {
+                file.store_string(&Json::stringify(&data.to_variant()));
+            }
+            None => error!(
+                "Failed to write run history: {:?}",
+                FileAccess::get_open_error()
+            ),
+        }
+    }
+
+    /// Append a finished run and write the history back to disk
+    pub fn record(run: RunRecord) {
+        let mut history = Self::load();
+        history.runs.push(run);
+        let overflow = history.runs.len().saturating_sub(Self::MAX_RUNS);
+        history.runs.drain(..overflow);
+        history.save();
+    }
+
+    /// Best runs for a level and mode, highest score first
+    pub fn high_scores(&self, mode: GameMode, level: i32, limit: usize) -> Vec<&RunRecord> {
+        let mut runs = self
+            .runs
+            .iter()
+            .filter(|run| run.mode == mode && run.level == level)
+            .collect::<Vec<_>>();
+        runs.sort_by(|a, b| b.score.total_cmp(&a.score));
+        runs.truncate(limit);
+        runs
+    }
+
+    /// Bring data of any known version up to [`RunHistory::VERSION`]
+    fn migrate(data: Variant) -> Result<Self, String> {
+        let mut data = match data.try_to::<Dictionary>() {
+            Ok(dict) => dict,
+            // 版本0：文件里只有一个记录数组
+            Err(_) => match data.try_to::<VariantArray>() {
+                Ok(runs) => vdict! { "version": 0, "runs": runs },
+                Err(_) => return Err("unexpected root value".to_string()),
+            },
+        };
+
+        let version = data
+            .get("version")
+            .and_then(|version| version.try_to::<f64>().ok())
+            .map(|version| version as i64)
+            .unwrap_or(0);
+        // 新版本的记录尽量按当前格式读取，但不写回
+        let read_only = version > Self::VERSION;
+        if read_only {
+            warn!("run history was written by a newer version ({version}), it is read only");
+        }
+
+        if version == 0 {
+            // 版本0的记录没有难度字段
+            let runs = data
+                .get("runs")
+                .unwrap_or_default()
+                .try_to::<VariantArray>();
+            for run in runs.iter().flat_map(|runs| runs.iter_shared()) {
+                if let Ok(mut run) = run.try_to::<Dictionary>() {
+                    if !run.contains_key("difficulty") {
+                        run.set("difficulty", Difficulty::default());
+                    }
+                }
+            }
+            data.set("version", 1);
+        }
+
+        let runs = data.get("runs").ok_or("missing runs").and_then(|runs| {
+            runs.try_to::<VariantArray>()
+                .map_err(|_| "runs is not an array")
+        });
+        let runs = match runs {
+            Ok(runs) => runs,
+            // 新版本可能换了格式，读不出来也不能当成损坏的文件移走
+            Err(_) if read_only => VariantArray::new(),
+            Err(message) => return Err(message.to_string()),
+        };
+        let runs = runs
+            .iter_shared()
+            .filter_map(|run| {
+                let record = RunRecord::from_dictionary(&run.try_to().ok()?);
+                if record.is_none() {
+                    error!("skipping unreadable run record: {}", run);
+                }
+                record
+            })
+            .collect();
+
+        Ok(Self { runs, read_only })
+    }
+
+    /// Keep the unreadable file around for bug reports instead of overwriting it
+    fn back_up_corrupt() {
+        let backup = format!("{}.corrupt", Self::PATH);
+        if DirAccess::rename_absolute(Self::PATH, backup.as_str()) != godot::global::Error::OK {
+            error!("Failed to back up corrupt run history to {}", backup);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+/*!
+一局的回放：关卡、难度、随机种子，以及玩家每次按下/松开按键的时间
+保存在 `user://replays/` 下，每局一个 JSON 文件
+*/
+
+use crate::game_controller::{Difficulty, GameMode};
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{DirAccess, FileAccess, Json};
+use godot::prelude::*;
+use log::error;
+
+/// One press or release of a part action
+#[derive(Clone, Debug)]
+pub struct ReplayEvent {
+    /// Game time since the run started, in seconds
+    pub time: f64,
+    pub action: StringName,
+    pub pressed: bool,
+}
+
+/// Inputs of one run, enough to play it again with the same seed
+#[derive(Clone, Debug, Default)]
+pub struct Replay {
+    pub mode: GameMode,
+    pub level: i32,
+    pub difficulty: Difficulty,
+    pub seed: i64,
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Replay {
+    pub const DIR: &'static str = "user://replays";
+    pub const VERSION: i64 = 1;
+
+    pub fn new(mode: GameMode, level: i32, difficulty: Difficulty, seed: i64) -> Self {
+        Self {
+            mode,
+            level,
+            difficulty,
+            seed,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, time: f64, action: StringName, pressed: bool) {
+        self.events.push(ReplayEvent {
+            time,
+            action,
+            pressed,
+        });
+    }
+
+    fn to_dictionary(&self) -> Dictionary {
+        let events = self
+            .events
+            .iter()
+            .map(|event| {
+                vdict! {
+                    "time": event.time,
+                    "action": event.action.clone(),
+                    "pressed": event.pressed,
+                }
+                .to_variant()
+            })
+            .collect::<VariantArray>();
+
+        vdict! {
+            "version": Self::VERSION,
+            "mode": self.mode,
+            "level": self.level,
+            "difficulty": self.difficulty,
+            // JSON numbers are doubles, keep the seed exact
+            "seed": self.seed.to_string(),
+            "events": events,
+        }
+    }
+
+    /// Write the replay to `user://replays/<name>.json` and return the path written
+    pub fn save(&self, name: &str) -> Option<GString> {
+        let error = DirAccess::make_dir_recursive_absolute(Self::DIR);
+        if error != godot::global::Error::OK {
+            error!("Failed to create replay directory: {:?}", error);
+            return None;
+        }
+
+        let path = format!("{}/{}.json", Self::DIR, name);
+        match FileAccess::open(path.as_str(), ModeFlags::WRITE) {
+            Some(mut file) => {
+                file.store_string(&Json::stringify(&self.to_dictionary().to_variant()));
+                Some(path.into())
+            }
+            None => {
+                error!(
+                    "Failed to write replay {}: {:?}",
+                    path,
+                    FileAccess::get_open_error()
+                );
+                None
+            }
+        }
+    }
+}
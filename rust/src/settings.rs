@@ -0,0 +1,259 @@
+use crate::game_controller::Difficulty;
+use crate::logging;
+use godot::classes::{AudioServer, ConfigFile, Engine, InputEventKey, InputMap, TranslationServer};
+use godot::global::{Key, linear_to_db};
+use godot::obj::EngineEnum;
+use godot::prelude::*;
+use log::{LevelFilter, error, warn};
+
+#[derive(GodotConvert, Var, Export, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[godot(via = GString)]
+pub enum ColorblindPalette {
+    #[default]
+    Off,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl ColorblindPalette {
+    /// Color a part glows towards as it gets close to failing
+    pub fn danger_color(self) -> Color {
+        match self {
+            ColorblindPalette::Off => Color::from_rgb(1.0, 0.1, 0.1),
+            // 红绿色盲分不清红色，用黄-蓝方向的橙黄色
+            ColorblindPalette::Protanopia | ColorblindPalette::Deuteranopia => {
+                Color::from_rgb(1.0, 0.7, 0.0)
+            }
+            ColorblindPalette::Tritanopia => Color::from_rgb(1.0, 0.0, 0.4),
+        }
+    }
+}
+
+/// Settings is a singleton holding the user preferences stored in `user://settings.cfg`
+#[derive(GodotClass)]
+#[class(base=Node)]
+pub struct Settings {
+    base: Base<Node>,
+    pub language: GString,
+    /// Action name -> physical keycode, only for actions the player rebound
+    pub key_bindings: Dictionary,
+    /// Linear volume of the Master bus, 0..1
+    pub master_volume: f64,
+    /// Linear volume of the SFX bus, 0..1
+    pub sfx_volume: f64,
+    pub difficulty: Difficulty,
+    pub colorblind_palette: ColorblindPalette,
+    pub reduced_motion: bool,
+    /// Level for log targets without their own level: off/error/warn/info/debug/trace
+    pub log_level: GString,
+    /// Log target -> level, e.g. `parts::blue` -> `debug`
+    pub log_targets: Dictionary,
+    /// Also write the log to `user://logs`
+    pub log_to_file: bool,
+}
+
+#[godot_api]
+impl INode for Settings {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            base,
+            language: "zh".into(),
+            key_bindings: Dictionary::new(),
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            difficulty: Difficulty::default(),
+            colorblind_palette: ColorblindPalette::default(),
+            reduced_motion: false,
+            log_level: "info".into(),
+            log_targets: Dictionary::new(),
+            log_to_file: false,
+        }
+    }
+}
+
+#[godot_api]
+impl Settings {
+    pub const SINGLETON: &'static str = "Settings";
+    pub const PATH: &'static str = "user://settings.cfg";
+    pub const SFX_BUS: &'static str = "SFX";
+    /// Actions the player may rebind, with the name shown in the settings menu
+    pub const ACTIONS: [(&'static str, &'static str); 6] = [
+        ("red_button", "红色按钮"),
+        ("yellow_button", "黄色按钮"),
+        ("blue_button", "蓝色按钮"),
+        ("pink_button", "粉色按钮"),
+        ("rewind", "倒带"),
+        ("pause", "暂停"),
+    ];
+
+    pub fn singleton() -> Gd<Settings> {
+        match Engine::singleton().get_singleton(Self::SINGLETON) {
+            Some(singleton) => singleton.cast::<Self>(),
+            None => panic!("Can not find Settings"),
+        }
+    }
+
+    /// Emitted after the settings were applied to the engine
+    #[signal]
+    pub fn settings_changed();
+}
+
+impl Settings {
+    /// Read the config file, keeping defaults for anything missing or unreadable
+    pub fn load(&mut self) {
+        let mut config = ConfigFile::new_gd();
+        match config.load(Self::PATH) {
+            godot::global::Error::OK => {}
+            godot::global::Error::ERR_FILE_NOT_FOUND => return,
+            error => {
+                error!("Failed to load settings: {:?}", error);
+                return;
+            }
+        }
+
+        let get = |section: &str, key: &str| config.get_value(section, key);
+        if let Ok(language) = get("general", "language").try_to() {
+            self.language = language;
+        }
+        if let Ok(difficulty) = get("general", "difficulty").try_to() {
+            self.difficulty = difficulty;
+        }
+        if let Ok(volume) = get("audio", "master_volume").try_to::<f64>() {
+            self.master_volume = volume.clamp(0.0, 1.0);
+        }
+        if let Ok(volume) = get("audio", "sfx_volume").try_to::<f64>() {
+            self.sfx_volume = volume.clamp(0.0, 1.0);
+        }
+        if let Ok(palette) = get("accessibility", "colorblind_palette").try_to() {
+            self.colorblind_palette = palette;
+        }
+        if let Ok(reduced_motion) = get("accessibility", "reduced_motion").try_to() {
+            self.reduced_motion = reduced_motion;
+        }
+        if let Ok(key_bindings) = get("input", "key_bindings").try_to() {
+            self.key_bindings = key_bindings;
+        }
+        if let Ok(log_level) = get("logging", "level").try_to() {
+            self.log_level = log_level;
+        }
+        if let Ok(log_targets) = get("logging", "targets").try_to() {
+            self.log_targets = log_targets;
+        }
+        if let Ok(log_to_file) = get("logging", "to_file").try_to() {
+            self.log_to_file = log_to_file;
+        }
+    }
+
+    pub fn save(&self) {
+        let mut config = ConfigFile::new_gd();
+        config.set_value("general", "language", &self.language.to_variant());
+        config.set_value("general", "difficulty", &self.difficulty.to_variant());
+        config.set_value("audio", "master_volume", &self.master_volume.to_variant());
+        config.set_value("audio", "sfx_volume", &self.sfx_volume.to_variant());
+        config.set_value(
+            "accessibility",
+            "colorblind_palette",
+            &self.colorblind_palette.to_variant(),
+        );
+        config.set_value(
+            "accessibility",
+            "reduced_motion",
+            &self.reduced_motion.to_variant(),
+        );
+        config.set_value("input", "key_bindings", &self.key_bindings.to_variant());
+        config.set_value("logging", "level", &self.log_level.to_variant());
+        config.set_value("logging", "targets", &self.log_targets.to_variant());
+        config.set_value("logging", "to_file", &self.log_to_file.to_variant());
+
+        let error = config.save(Self::PATH);
+        if error != godot::global::Error::OK {
+            error!("Failed to save settings: {:?}", error);
+        }
+    }
+
+    /// Push the settings into the engine: locale, input map, audio buses and logging
+    pub fn apply(&mut self) {
+        TranslationServer::singleton().set_locale(&self.language);
+        self.apply_logging();
+
+        for (action, keycode) in self.key_bindings.iter_shared() {
+            let (Ok(action), Ok(keycode)) =
+                (action.try_to::<StringName>(), keycode.try_to::<i64>())
+            else {
+                continue;
+            };
+            let Some(key) = Key::try_from_ord(keycode as i32) else {
+                continue;
+            };
+            Self::bind_action(&action, key);
+        }
+
+        let mut audio = AudioServer::singleton();
+        let mut sfx_bus = audio.get_bus_index(Self::SFX_BUS);
+        if sfx_bus < 0 {
+            audio.add_bus();
+            sfx_bus = audio.get_bus_count() - 1;
+            audio.set_bus_name(sfx_bus, Self::SFX_BUS);
+            audio.set_bus_send(sfx_bus, "Master");
+        }
+        audio.set_bus_volume_db(0, linear_to_db(self.master_volume) as f32);
+        audio.set_bus_volume_db(sfx_bus, linear_to_db(self.sfx_volume) as f32);
+
+        // 监听者会在信号里读取设置，先让出当前的可变借用
+        let settings = self.to_gd();
+        let _guard = self.base_mut();
+        settings.signals().settings_changed().emit();
+    }
+
+    /// Rebind an action to a single physical key and remember it
+    pub fn set_key(&mut self, action: &str, key: Key) {
+        self.key_bindings.set(action, key.ord() as i64);
+        Self::bind_action(&StringName::from(action), key);
+    }
+
+    /// Key currently bound to an action in the input map
+    pub fn key_for(action: &str) -> Option<Key> {
+        InputMap::singleton()
+            .action_get_events(action)
+            .iter_shared()
+            .find_map(|event| event.try_cast::<InputEventKey>().ok())
+            .map(|event| match event.get_physical_keycode() {
+                Key::NONE => event.get_keycode(),
+                key => key,
+            })
+    }
+
+    fn apply_logging(&self) {
+        logging::set_default_level(
+            Self::parse_level(&self.log_level.to_string()).unwrap_or(LevelFilter::Info),
+        );
+        logging::clear_target_levels();
+        for (target, level) in self.log_targets.iter_shared() {
+            let target = target.to_string();
+            match Self::parse_level(&level.to_string()) {
+                Some(level) => logging::set_target_level(&target, level),
+                None => warn!("Unknown log level for {}: {}", target, level),
+            }
+        }
+        logging::set_file_enabled(self.log_to_file);
+    }
+
+    /// Parse a level name such as `debug`, case insensitive
+    pub fn parse_level(level: &str) -> Option<LevelFilter> {
+        level.parse().ok()
+    }
+
+    fn bind_action(action: &StringName, key: Key) {
+        let mut input_map = InputMap::singleton();
+        if !input_map.has_action(action) {
+            error!("Unknown input action in settings: {}", action);
+            return;
+        }
+
+        let mut event = InputEventKey::new_gd();
+        event.set_physical_keycode(key);
+        input_map.action_erase_events(action);
+        input_map.action_add_event(action, &event);
+    }
+}
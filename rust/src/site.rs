@@ -0,0 +1,102 @@
+/*!
+关卡场地：`part.TbSiteLayout` 的每一行是关卡里的一个零件或摆设，带位置和旋转（角度）
+表里有这一关的行时按表搭建 `Parts`，没有时按 `Level::parts` 排成一排
+摆设不是零件，不参与计分和存档，只用来布置场地
+`card.TbSiteItems` 以卡片名为主键，每张矿物卡片一行权重，和这里每关每个零件一行的布置不是同一种数据，所以另建了表
+没有开启 `config` feature 时所有关卡都排成一排
+*/
+
+#![cfg_attr(not(feature = "config"), allow(dead_code))]
+
+use crate::config;
+use godot::prelude::*;
+use log::warn;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SiteItemKind {
+    Part,
+    Prop,
+}
+
+/// A part or prop placed on the site of a level
+#[derive(Clone, Debug)]
+pub struct SiteItem {
+    pub id: i32,
+    pub kind: SiteItemKind,
+    pub scene: String,
+    pub position: Vector3,
+    /// Euler angles in degrees
+    pub rotation: Vector3,
+}
+
+impl SiteItem {
+    #[cfg(not(feature = "config"))]
+    pub fn for_level(_level: i32) -> Vec<SiteItem> {
+        Vec::new()
+    }
+
+    /// Site of `level` in table order, empty when the level has no site
+    #[cfg(feature = "config")]
+    pub fn for_level(level: i32) -> Vec<SiteItem> {
+        let Some(tables) = config::tables() else {
+            return Vec::new();
+        };
+        tables
+            .TbSiteLayout
+            .data_list
+            .iter()
+            .filter(|row| row.level == level)
+            .filter_map(|row| {
+                let kind = match row.kind.as_str() {
+                    "part" => SiteItemKind::Part,
+                    "prop" => SiteItemKind::Prop,
+                    kind => {
+                        warn!("site item {} has unknown kind {}", row.id, kind);
+                        return None;
+                    }
+                };
+                Some(SiteItem {
+                    id: row.id,
+                    kind,
+                    scene: row.scene.clone(),
+                    position: Vector3::from(&row.position),
+                    rotation: Vector3::from(&row.rotation),
+                })
+            })
+            .collect()
+    }
+
+    /// Instance the scene under `parent` at the item's position and rotation
+    pub fn instantiate(&self, parent: &mut Gd<Node>) -> Option<Gd<Node3D>> {
+        let Ok(scene) = try_load::<PackedScene>(self.scene.as_str()) else {
+            warn!("site item {} can not load {}", self.id, self.scene);
+            return None;
+        };
+        let mut node = scene.try_instantiate_as::<Node3D>()?;
+        node.set_position(self.position);
+        node.set_rotation_degrees(self.rotation);
+        parent.add_child(&node);
+        if self.kind == SiteItemKind::Part {
+            config::apply_params(&mut node.clone().upcast());
+        }
+        Some(node)
+    }
+}
+
+/// Build the site of `level` under `parent`, returns false when the level has no site
+pub fn build(parent: &mut Gd<Node>, level: i32) -> bool {
+    let items = SiteItem::for_level(level);
+    for item in &items {
+        item.instantiate(parent);
+    }
+    !items.is_empty()
+}
+
+/// Put the props of `level` back, for when the parts were placed from a snapshot
+pub fn build_props(parent: &mut Gd<Node>, level: i32) {
+    for item in SiteItem::for_level(level) {
+        if item.kind == SiteItemKind::Prop {
+            item.instantiate(parent);
+        }
+    }
+}
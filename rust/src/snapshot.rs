@@ -0,0 +1,180 @@
+/*!
+一局的快照：关卡、计时器剩余时间、时间流速、随机数状态，以及每个零件的字段和计时器
+可以在任意时刻保存和恢复，用于测试时的快速存档/读档和复现 bug
+保存在 `user://snapshots/` 下，用 Godot 的文本格式（var_to_str），Vector3 等类型不会丢失
+*/
+
+use crate::game_controller::{Difficulty, GameMode};
+use crate::parts::state_value;
+#[cfg(feature = "dev-tools")]
+use godot::classes::file_access::ModeFlags;
+#[cfg(feature = "dev-tools")]
+use godot::classes::{DirAccess, FileAccess};
+#[cfg(feature = "dev-tools")]
+use godot::global::{str_to_var, var_to_str};
+use godot::prelude::*;
+#[cfg(feature = "dev-tools")]
+use log::error;
+
+/// A part as it was placed under `Parts`
+#[derive(Clone, Debug)]
+pub struct PartSnapshot {
+    pub scene: GString,
+    pub position: Vector3,
+    /// Euler angles in degrees
+    pub rotation: Vector3,
+    /// Taken with `Part::save_state`
+    pub state: Dictionary,
+}
+
+/// Everything needed to continue a run from the moment it was taken
+#[derive(Clone, Debug, Default)]
+pub struct RunSnapshot {
+    pub mode: GameMode,
+    pub level: i32,
+    pub difficulty: Difficulty,
+    /// Round length before the difficulty multiplier
+    pub time_limit: f64,
+    pub run_seed: i64,
+    /// Seconds left on the round timer
+    pub time_left: f64,
+    /// Engine time scale, the game clock speed
+    pub time_scale: f64,
+    /// State of the run RNG, see `GameSignals::rng`
+    pub rng_state: u64,
+    pub rewind_charges: i32,
+    /// Extra lives left, see `GameSignals::extra_lives`
+    pub extra_lives: i32,
+    /// Names of the cards held for the run
+    pub cards: PackedStringArray,
+    /// Rounds cleared through the card draft
+    pub round: i32,
+    pub parts: Vec<PartSnapshot>,
+}
+
+impl RunSnapshot {
+    pub const VERSION: i64 = 1;
+
+    pub fn to_dictionary(&self) -> Dictionary {
+        let parts = self
+            .parts
+            .iter()
+            .map(|part| {
+                vdict! {
+                    "scene": part.scene.clone(),
+                    "position": part.position,
+                    "rotation": part.rotation,
+                    "state": part.state.clone(),
+                }
+                .to_variant()
+            })
+            .collect::<VariantArray>();
+
+        vdict! {
+            "version": Self::VERSION,
+            "mode": self.mode,
+            "level": self.level,
+            "difficulty": self.difficulty,
+            "time_limit": self.time_limit,
+            "run_seed": self.run_seed,
+            "time_left": self.time_left,
+            "time_scale": self.time_scale,
+            "rng_state": self.rng_state as i64,
+            "rewind_charges": self.rewind_charges,
+            "extra_lives": self.extra_lives,
+            "cards": self.cards.clone(),
+            "round": self.round,
+            "parts": parts,
+        }
+    }
+
+    pub fn from_dictionary(dict: &Dictionary) -> Option<Self> {
+        if state_value::<i64>(dict, "version") != Self::VERSION {
+            return None;
+        }
+
+        let parts = state_value::<VariantArray>(dict, "parts")
+            .iter_shared()
+            .filter_map(|part| part.try_to::<Dictionary>().ok())
+            .map(|part| PartSnapshot {
+                scene: state_value(&part, "scene"),
+                position: state_value(&part, "position"),
+                rotation: state_value(&part, "rotation"),
+                state: state_value(&part, "state"),
+            })
+            .collect();
+
+        Some(Self {
+            mode: dict.get("mode")?.try_to().ok()?,
+            level: state_value(dict, "level"),
+            difficulty: dict.get("difficulty")?.try_to().ok()?,
+            time_limit: state_value(dict, "time_limit"),
+            run_seed: state_value(dict, "run_seed"),
+            time_left: state_value(dict, "time_left"),
+            time_scale: state_value(dict, "time_scale"),
+            rng_state: state_value::<i64>(dict, "rng_state") as u64,
+            rewind_charges: state_value(dict, "rewind_charges"),
+            extra_lives: state_value(dict, "extra_lives"),
+            cards: state_value(dict, "cards"),
+            round: state_value(dict, "round"),
+            parts,
+        })
+    }
+}
+
+/// Quick-save and quick-load files, used from the developer console
+#[cfg(feature = "dev-tools")]
+impl RunSnapshot {
+    pub const DIR: &'static str = "user://snapshots";
+
+    /// Write the snapshot to `user://snapshots/<name>.txt` and return the path written
+    pub fn save(&self, name: &str) -> Option<GString> {
+        let error = DirAccess::make_dir_recursive_absolute(Self::DIR);
+        if error != godot::global::Error::OK {
+            error!("Failed to create snapshot directory: {:?}", error);
+            return None;
+        }
+
+        let path = Self::path(name);
+        match FileAccess::open(path.as_str(), ModeFlags::WRITE) {
+            Some(mut file) => {
+                file.store_string(&var_to_str(&self.to_dictionary().to_variant()));
+                Some(path.into())
+            }
+            None => {
+                error!(
+                    "Failed to write snapshot {}: {:?}",
+                    path,
+                    FileAccess::get_open_error()
+                );
+                None
+            }
+        }
+    }
+
+    /// Read a snapshot written by `save`
+    pub fn load(name: &str) -> Option<Self> {
+        let path = Self::path(name);
+        let Some(file) = FileAccess::open(path.as_str(), ModeFlags::READ) else {
+            error!(
+                "Failed to read snapshot {}: {:?}",
+                path,
+                FileAccess::get_open_error()
+            );
+            return None;
+        };
+
+        let snapshot = str_to_var(&file.get_as_text())
+            .try_to::<Dictionary>()
+            .ok()
+            .and_then(|dict| Self::from_dictionary(&dict));
+        if snapshot.is_none() {
+            error!("Snapshot {} can not be read", path);
+        }
+        snapshot
+    }
+
+    fn path(name: &str) -> String {
+        format!("{}/{}.txt", Self::DIR, name)
+    }
+}
@@ -0,0 +1,110 @@
+/*!
+新手教程：每一步只放一个零件并显示说明
+玩家演示过该零件的玩法之前不会失败，进度保存在 `user://tutorial.cfg`，完成后不再自动进入
+*/
+
+use godot::classes::ConfigFile;
+use godot::prelude::*;
+use log::error;
+
+/// Part signal that proves the player understood the mechanic
+pub enum Goal {
+    /// The signal fired at all
+    Signal(&'static str),
+    /// The signal fired with this bool argument
+    SignalWith(&'static str, bool),
+}
+
+pub struct TutorialStep {
+    /// Part scene placed alone under `Parts`
+    pub part: &'static str,
+    /// Input action of the part, shown with its current key
+    pub action: &'static str,
+    pub instructions: &'static str,
+    pub goal: Goal,
+}
+
+pub const STEPS: [TutorialStep; 4] = [
+    TutorialStep {
+        part: "res://parts/Button1003.tscn",
+        action: "blue_button",
+        instructions: "蓝色按钮会慢慢放电，电量耗尽或充满都会失败\n按下 {key} 给它充电",
+        goal: Goal::Signal("charging_started"),
+    },
+    TutorialStep {
+        part: "res://parts/Button1001.tscn",
+        action: "red_button",
+        instructions: "红色按钮每秒都要按一次，越早按得分越高\n在它亮着的时候按下 {key}",
+        goal: Goal::SignalWith("window_closed", true),
+    },
+    TutorialStep {
+        part: "res://parts/Button1002.tscn",
+        action: "yellow_button",
+        instructions: "黄色按钮亮灯后要及时按住，并且持续按住两秒\n亮灯后按住 {key} 不要松开",
+        goal: Goal::Signal("hold_completed"),
+    },
+    TutorialStep {
+        part: "res://parts/Button1005.tscn",
+        action: "pink_button",
+        instructions: "粉色齿轮会不断加快时间流速\n按下 {key} 让时间慢下来",
+        goal: Goal::SignalWith("gear_turned", false),
+    },
+];
+
+/// Seconds a tutorial step may run, long enough to never end before the goal
+pub const STEP_TIME_LIMIT: f64 = 120.0;
+
+/// How far the player got in the tutorial
+#[derive(Default)]
+pub struct TutorialProgress {
+    /// Number of steps finished, equal to `STEPS.len()` once the tutorial is done
+    pub completed_steps: i32,
+}
+
+impl TutorialProgress {
+    pub const PATH: &'static str = "user://tutorial.cfg";
+
+    pub fn load() -> Self {
+        let mut config = ConfigFile::new_gd();
+        match config.load(Self::PATH) {
+            godot::global::Error::OK => {}
+            godot::global::Error::ERR_FILE_NOT_FOUND => return Self::default(),
+            error => {
+                error!("Failed to load tutorial progress: {:?}", error);
+                return Self::default();
+            }
+        }
+
+        let completed_steps = config
+            .get_value("tutorial", "completed_steps")
+            .try_to::<i32>()
+            .unwrap_or_default();
+        Self {
+            completed_steps: completed_steps.clamp(0, STEPS.len() as i32),
+        }
+    }
+
+    pub fn save(&self) {
+        let mut config = ConfigFile::new_gd();
+        config.set_value(
+            "tutorial",
+            "completed_steps",
+            &self.completed_steps.to_variant(),
+        );
+        let error = config.save(Self::PATH);
+        if error != godot::global::Error::OK {
+            error!("Failed to save tutorial progress: {:?}", error);
+        }
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.completed_steps >= STEPS.len() as i32
+    }
+
+    /// Remember that `step` was finished, progress never goes backwards
+    pub fn complete_step(step: i32) {
+        let mut progress = Self::load();
+        progress.completed_steps = progress.completed_steps.max(step + 1);
+        progress.save();
+    }
+}
@@ -0,0 +1,91 @@
+use crate::config;
+use crate::game_controller::{Game, GameState};
+use crate::game_signals::GameSignals;
+use godot::classes::control::SizeFlags;
+use godot::classes::{Button, Control, HBoxContainer, IControl, Label};
+use godot::prelude::*;
+use log::debug;
+
+/// DraftScreen offers the cards drawn after a won round, shown over the victory screen
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct DraftScreen {
+    base: Base<Control>,
+    #[export]
+    game: Option<Gd<Game>>,
+    #[init(node = "%TitleLabel")]
+    title_label: OnReady<Gd<Label>>,
+    #[init(node = "%Cards")]
+    cards: OnReady<Gd<HBoxContainer>>,
+}
+
+#[godot_api]
+impl IControl for DraftScreen {
+    fn ready(&mut self) {
+        // Hide the draft screen initially
+        self.base_mut().set_visible(false);
+        GameSignals::singleton()
+            .signals()
+            .state_changed()
+            .connect_other(self, |this, state| {
+                if state != GameState::Result {
+                    this.base_mut().set_visible(false);
+                }
+            });
+        if let Some(game) = &self.game {
+            game.signals()
+                .draft_offered()
+                .connect_other(self, Self::show_screen);
+        }
+
+        // 跳过抽卡，留在胜利界面
+        let skip_button = self.base().get_node_as::<Button>("%SkipButton");
+        skip_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_skip_button_pressed);
+    }
+}
+
+#[godot_api]
+impl DraftScreen {
+    /// Rebuild a button for every offered card and show the screen
+    #[func]
+    pub fn show_screen(&mut self) {
+        debug!("Showing draft screen");
+        let Some(game) = self.game.clone() else {
+            return;
+        };
+        for mut button in self.cards.get_children().iter_shared() {
+            button.queue_free();
+        }
+
+        let game = game.bind();
+        self.title_label
+            .set_text(&format!("第{}轮通过，选择一张卡片", game.round() + 1));
+        for card in game.draft_offer() {
+            let mut button = Button::new_alloc();
+            let text = format!(
+                "{}\n\n{}",
+                config::text(&card.display_name),
+                config::text(&card.description)
+            );
+            button.set_text(&text);
+            button.set_custom_minimum_size(Vector2::new(200.0, 160.0));
+            button.set_h_size_flags(SizeFlags::EXPAND_FILL);
+            let name = GString::from(card.name.as_str());
+            button.signals().pressed().connect(move || {
+                GameSignals::singleton().signals().card_picked().emit(&name);
+            });
+            self.cards.add_child(&button);
+        }
+
+        self.base_mut().set_visible(true);
+    }
+
+    #[func]
+    pub fn on_skip_button_pressed(&mut self) {
+        debug!("Skip button pressed");
+        self.base_mut().set_visible(false);
+    }
+}
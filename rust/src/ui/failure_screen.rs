@@ -1,25 +1,63 @@
-use godot::prelude::*;
-use godot::classes::{Control, IControl, Button};
+use crate::game_controller::{Game, GameState};
 use crate::game_signals::GameSignals;
+use crate::ui::run_stats;
+use godot::classes::{Button, Control, IControl, Label};
+use godot::prelude::*;
+use log::debug;
 
 /// FailureScreen is shown when the player loses the game
 #[derive(GodotClass)]
 #[class(init, base=Control)]
 pub struct FailureScreen {
     base: Base<Control>,
+    #[export]
+    game: Option<Gd<Game>>,
+    #[init(node = "%StatsLabel")]
+    stats_label: OnReady<Gd<Label>>,
+    #[init(node = "%SaveReplayButton")]
+    save_replay_button: OnReady<Gd<Button>>,
 }
 
 #[godot_api]
 impl IControl for FailureScreen {
     fn ready(&mut self) {
-
         // Hide the failure screen initially
         self.base_mut().set_visible(false);
-        GameSignals::singleton().signals().game_failure().connect_other(self, Self::show_screen);
+        GameSignals::singleton()
+            .signals()
+            .game_failure()
+            .connect_other(self, |this, _reason| this.show_screen());
+        GameSignals::singleton()
+            .signals()
+            .state_changed()
+            .connect_other(self, |this, state| {
+                if state != GameState::Result {
+                    this.base_mut().set_visible(false);
+                }
+            });
 
         // Connect the retry button signal
         let retry_button = self.base().get_node_as::<Button>("RetryButton");
-        retry_button.signals().pressed().connect_other(self, Self::on_failure_button_pressed);
+        retry_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_failure_button_pressed);
+
+        let menu_button = self.base().get_node_as::<Button>("MenuButton");
+        menu_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_menu_button_pressed);
+
+        self.save_replay_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_save_replay_button_pressed);
+        if let Some(game) = &self.game {
+            game.signals()
+                .run_finished()
+                .connect_other(self, Self::on_run_finished);
+        }
     }
 }
 
@@ -28,16 +66,38 @@ impl FailureScreen {
     /// Show the failure screen
     #[func]
     pub fn show_screen(&mut self) {
-        godot_print!("Showing failure screen");
+        debug!("Showing failure screen");
         self.base_mut().set_visible(true);
     }
 
     #[func]
     pub fn on_failure_button_pressed(&mut self) {
-        godot_print!("Restart button pressed");
+        debug!("Restart button pressed");
+        self.base_mut().set_visible(false);
+
+        // We'll request a new run to restart
+        GameSignals::singleton().signals().game_requested().emit();
+    }
+
+    #[func]
+    pub fn on_menu_button_pressed(&mut self) {
+        debug!("Menu button pressed");
         self.base_mut().set_visible(false);
+        GameSignals::singleton().signals().menu_requested().emit();
+    }
 
-        // We'll emit the game started signal to restart
-        GameSignals::singleton().signals().game_started().emit();
+    /// Fill in the stats of the run that just finished
+    fn on_run_finished(&mut self) {
+        if let Some(game) = &self.game {
+            run_stats::show_run_stats(game, &mut self.stats_label, &mut self.save_replay_button);
+        }
+    }
+
+    #[func]
+    pub fn on_save_replay_button_pressed(&mut self) {
+        debug!("Save replay button pressed");
+        if let Some(game) = &self.game {
+            run_stats::save_replay(game, &mut self.save_replay_button);
+        }
     }
 }
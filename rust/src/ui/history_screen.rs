@@ -0,0 +1,114 @@
+use crate::game_signals::GameSignals;
+use crate::records::{RunHistory, RunRecord};
+use godot::classes::{Button, Control, IControl, Label, Time};
+use godot::prelude::*;
+use log::debug;
+
+/// HistoryScreen lists the high-score table of every level/mode and the latest runs
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct HistoryScreen {
+    base: Base<Control>,
+    #[init(node = "%HighScoreLabel")]
+    high_score_label: OnReady<Gd<Label>>,
+    #[init(node = "%HistoryLabel")]
+    history_label: OnReady<Gd<Label>>,
+    /// Entries shown per high-score table
+    #[export]
+    #[init(val = 5)]
+    high_score_count: u32,
+    /// Number of latest runs shown
+    #[export]
+    #[init(val = 20)]
+    history_count: u32,
+}
+
+#[godot_api]
+impl IControl for HistoryScreen {
+    fn ready(&mut self) {
+        // Hide the history screen initially
+        self.base_mut().set_visible(false);
+        GameSignals::singleton()
+            .signals()
+            .history_requested()
+            .connect_other(self, Self::show_screen);
+
+        // Connect the back button signal
+        let back_button = self.base().get_node_as::<Button>("BackButton");
+        back_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_back_button_pressed);
+    }
+}
+
+#[godot_api]
+impl HistoryScreen {
+    /// Show the history screen with freshly loaded records
+    #[func]
+    pub fn show_screen(&mut self) {
+        debug!("Showing history screen");
+        let history = RunHistory::load();
+
+        let mut tables = Vec::new();
+        for run in &history.runs {
+            if !tables.contains(&(run.mode, run.level)) {
+                tables.push((run.mode, run.level));
+            }
+        }
+        tables.sort_by_key(|(_, level)| *level);
+
+        let mut high_scores = String::new();
+        for (mode, level) in tables {
+            high_scores += &format!("{:?} 第{}关\n", mode, level);
+            for (rank, run) in history
+                .high_scores(mode, level, self.high_score_count as usize)
+                .iter()
+                .enumerate()
+            {
+                high_scores += &format!("  {}. {}\n", rank + 1, Self::describe(run));
+            }
+        }
+        if high_scores.is_empty() {
+            high_scores = "还没有记录".to_string();
+        }
+        self.high_score_label.set_text(&high_scores);
+
+        let history_text = history
+            .runs
+            .iter()
+            .rev()
+            .take(self.history_count as usize)
+            .map(|run| {
+                format!(
+                    "{} {:?} 第{}关 {}",
+                    Time::singleton().get_datetime_string_from_unix_time(run.finished_at as i64),
+                    run.mode,
+                    run.level,
+                    Self::describe(run)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.history_label.set_text(&history_text);
+
+        self.base_mut().set_visible(true);
+    }
+
+    #[func]
+    pub fn on_back_button_pressed(&mut self) {
+        debug!("Back button pressed");
+        self.base_mut().set_visible(false);
+    }
+
+    fn describe(run: &RunRecord) -> String {
+        let mut text = format!(
+            "{:.0}分 {:?} {:?} {:.1}秒 种子{}",
+            run.score, run.outcome, run.difficulty, run.duration, run.seed
+        );
+        if !run.failure_reason.is_empty() {
+            text += &format!(" ({})", run.failure_reason);
+        }
+        text
+    }
+}
@@ -0,0 +1,148 @@
+use crate::game_controller::Game;
+use crate::game_signals::GameSignals;
+use crate::parts::Part;
+use godot::classes::control::SizeFlags;
+use godot::classes::{Control, Engine, HBoxContainer, IControl, Label, ProgressBar, VBoxContainer};
+use godot::prelude::*;
+
+/// Hud shows the round timer, the time scale and a danger meter for every part while a run is going
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct Hud {
+    base: Base<Control>,
+    #[export]
+    game: Option<Gd<Game>>,
+    /// Node whose children are the parts to watch
+    #[export]
+    parts: Option<Gd<Node>>,
+    #[init(node = "%TimeLabel")]
+    time_label: OnReady<Gd<Label>>,
+    #[init(node = "%TimeScaleLabel")]
+    time_scale_label: OnReady<Gd<Label>>,
+    #[init(node = "%RewindLabel")]
+    rewind_label: OnReady<Gd<Label>>,
+    #[init(node = "%Meters")]
+    meters: OnReady<Gd<VBoxContainer>>,
+    #[init(node = "%CountdownLabel")]
+    countdown_label: OnReady<Gd<Label>>,
+}
+
+#[godot_api]
+impl IControl for Hud {
+    fn ready(&mut self) {
+        // Hide the HUD until a run starts
+        self.base_mut().set_visible(false);
+        GameSignals::singleton()
+            .signals()
+            .countdown_started()
+            .connect_other(self, Self::show_hud);
+        GameSignals::singleton()
+            .signals()
+            .countdown_tick()
+            .connect_other(self, Self::on_countdown_tick);
+        GameSignals::singleton()
+            .signals()
+            .game_started()
+            .connect_other(self, Self::on_game_started);
+        GameSignals::singleton()
+            .signals()
+            .game_stopped()
+            .connect_other(self, Self::hide_hud);
+
+        if let Some(game) = &self.game {
+            game.signals()
+                .time_left_changed()
+                .connect_other(self, Self::on_time_left_changed);
+            game.signals()
+                .rewind_charges_changed()
+                .connect_other(self, Self::on_rewind_charges_changed);
+        }
+    }
+}
+
+#[godot_api]
+impl Hud {
+    /// Rebuild the danger meters for the parts of this run and show the HUD
+    #[func]
+    pub fn show_hud(&mut self) {
+        for mut row in self.meters.get_children().iter_shared() {
+            row.queue_free();
+        }
+
+        let parts = self
+            .parts
+            .as_ref()
+            .map(|parts| parts.get_children())
+            .unwrap_or_default();
+        for mut child in parts.iter_shared() {
+            let Ok(part) = child.clone().try_dynify::<dyn Part>() else {
+                continue;
+            };
+
+            let mut label = Label::new_alloc();
+            label.set_text(&part.dyn_bind().title());
+
+            let mut meter = ProgressBar::new_alloc();
+            meter.set_max(1.0);
+            meter.set_step(0.01);
+            meter.set_show_percentage(false);
+            meter.set_custom_minimum_size(Vector2::new(120.0, 12.0));
+            meter.set_v_size_flags(SizeFlags::SHRINK_CENTER);
+
+            // 零件自己发出信号，HUD 不主动轮询
+            child.connect("danger_changed", &meter.callable("set_value"));
+            if child.has_signal("time_scale_changed") {
+                let on_time_scale_changed = self.to_gd().callable("on_time_scale_changed");
+                if !child.is_connected("time_scale_changed", &on_time_scale_changed) {
+                    child.connect("time_scale_changed", &on_time_scale_changed);
+                }
+            }
+
+            let mut row = HBoxContainer::new_alloc();
+            row.add_child(&label);
+            row.add_child(&meter);
+            self.meters.add_child(&row);
+        }
+
+        self.on_time_scale_changed(Engine::singleton().get_time_scale());
+        self.base_mut().set_visible(true);
+    }
+
+    #[func]
+    fn on_countdown_tick(&mut self, seconds_left: i64) {
+        self.countdown_label.set_text(&seconds_left.to_string());
+        self.countdown_label.set_visible(true);
+    }
+
+    #[func]
+    fn on_game_started(&mut self) {
+        self.countdown_label.set_visible(false);
+        // 没有倒计时的时候在这里才显示
+        if !self.base().is_visible() {
+            self.show_hud();
+        }
+    }
+
+    #[func]
+    pub fn hide_hud(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    #[func]
+    fn on_time_left_changed(&mut self, time_left: f64) {
+        self.time_label
+            .set_text(&format!("剩余时间 {:.1}秒", time_left));
+    }
+
+    #[func]
+    fn on_time_scale_changed(&mut self, scale: f64) {
+        self.time_scale_label
+            .set_text(&format!("时间流速 x{:.1}", scale));
+    }
+
+    #[func]
+    fn on_rewind_charges_changed(&mut self, charges: i32) {
+        self.rewind_label.set_text(&format!("倒带 x{}", charges));
+        self.rewind_label.set_visible(charges > 0);
+    }
+}
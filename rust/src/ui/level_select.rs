@@ -0,0 +1,81 @@
+use crate::game_controller::{GameMode, GameState};
+use crate::game_signals::GameSignals;
+use crate::levels::LEVELS;
+use crate::records::RunHistory;
+use godot::classes::{Button, Control, IControl, VBoxContainer};
+use godot::prelude::*;
+use log::debug;
+
+/// LevelSelect lists every level with its lock state and best score
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct LevelSelect {
+    base: Base<Control>,
+    #[init(node = "%Levels")]
+    levels: OnReady<Gd<VBoxContainer>>,
+}
+
+#[godot_api]
+impl IControl for LevelSelect {
+    fn ready(&mut self) {
+        // Hide the level select initially
+        self.base_mut().set_visible(false);
+        GameSignals::singleton()
+            .signals()
+            .state_changed()
+            .connect_other(self, |this, state| {
+                if state == GameState::LevelSelect {
+                    this.show_screen();
+                } else {
+                    this.base_mut().set_visible(false);
+                }
+            });
+
+        // Connect the back button signal
+        let back_button = self.base().get_node_as::<Button>("BackButton");
+        back_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_back_button_pressed);
+    }
+}
+
+#[godot_api]
+impl LevelSelect {
+    /// Rebuild the level buttons from the run history and show the screen
+    #[func]
+    pub fn show_screen(&mut self) {
+        debug!("Showing level select");
+        for mut button in self.levels.get_children().iter_shared() {
+            button.queue_free();
+        }
+
+        let history = RunHistory::load();
+        for level in &LEVELS {
+            let unlocked = level.is_unlocked(&history);
+            let mut text = format!("第{}关 {}", level.id, level.name);
+            if !unlocked {
+                text += " (未解锁)";
+            } else if let Some(best) = history.high_scores(GameMode::Normal, level.id, 1).first() {
+                text += &format!(" 最高分 {:.0}", best.score);
+            }
+
+            let mut button = Button::new_alloc();
+            button.set_text(&text);
+            button.set_disabled(!unlocked);
+            let id = level.id;
+            button.signals().pressed().connect(move || {
+                GameSignals::singleton().signals().level_selected().emit(id);
+            });
+            self.levels.add_child(&button);
+        }
+
+        self.base_mut().set_visible(true);
+    }
+
+    #[func]
+    pub fn on_back_button_pressed(&mut self) {
+        debug!("Back button pressed");
+        GameSignals::singleton().signals().menu_requested().emit();
+    }
+}
@@ -1,3 +1,12 @@
+mod draft_screen;
+mod failure_screen;
+mod history_screen;
+mod hud;
+mod level_select;
+mod pause_menu;
+mod practice_panel;
+mod run_stats;
+mod settings_menu;
 mod start_menu;
+mod tutorial_overlay;
 mod victory_screen;
-mod failure_screen;
\ No newline at end of file
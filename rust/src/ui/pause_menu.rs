@@ -0,0 +1,129 @@
+use crate::game_signals::GameSignals;
+use godot::classes::{Button, Control, Engine, IControl, InputEvent};
+use godot::prelude::*;
+use log::debug;
+
+/// PauseMenu is opened with Escape during a run and freezes the scene tree while shown
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct PauseMenu {
+    base: Base<Control>,
+    /// Whether a run (or its countdown) is in progress and can be paused
+    running: bool,
+    /// Engine time scale to restore on resume
+    #[init(val = 1.0)]
+    saved_time_scale: f64,
+}
+
+#[godot_api]
+impl IControl for PauseMenu {
+    fn ready(&mut self) {
+        // Hide the pause menu initially
+        self.base_mut().set_visible(false);
+
+        GameSignals::singleton()
+            .signals()
+            .countdown_started()
+            .connect_other(self, |this| this.running = true);
+        GameSignals::singleton()
+            .signals()
+            .game_started()
+            .connect_other(self, |this| this.running = true);
+        GameSignals::singleton()
+            .signals()
+            .game_stopped()
+            .connect_other(self, |this| this.running = false);
+
+        let resume_button = self.base().get_node_as::<Button>("%ResumeButton");
+        resume_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::resume);
+        let restart_button = self.base().get_node_as::<Button>("%RestartButton");
+        restart_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_restart_button_pressed);
+        let settings_button = self.base().get_node_as::<Button>("%SettingsButton");
+        settings_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_settings_button_pressed);
+        let quit_button = self.base().get_node_as::<Button>("%QuitButton");
+        quit_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_quit_button_pressed);
+    }
+
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        if !self.running || !event.is_action_pressed("pause") {
+            return;
+        }
+
+        if self.base().is_visible() {
+            self.resume();
+        } else {
+            self.pause();
+        }
+        self.base().get_viewport().unwrap().set_input_as_handled();
+    }
+}
+
+#[godot_api]
+impl PauseMenu {
+    /// Freeze the run and show the menu
+    #[func]
+    pub fn pause(&mut self) {
+        debug!("Game paused");
+        // 菜单本身按正常速度运行，恢复时再还原时间流速
+        self.saved_time_scale = Engine::singleton().get_time_scale();
+        Engine::singleton().set_time_scale(1.0);
+        self.base().get_tree().unwrap().set_pause(true);
+        self.base_mut().set_visible(true);
+
+        GameSignals::singleton().signals().game_paused().emit();
+    }
+
+    /// Hide the menu and continue the run where it stopped
+    #[func]
+    pub fn resume(&mut self) {
+        debug!("Game resumed");
+        self.base_mut().set_visible(false);
+        self.base().get_tree().unwrap().set_pause(false);
+        Engine::singleton().set_time_scale(self.saved_time_scale);
+
+        GameSignals::singleton().signals().game_resumed().emit();
+    }
+
+    #[func]
+    fn on_restart_button_pressed(&mut self) {
+        debug!("Restart button pressed");
+        self.leave_run();
+        GameSignals::singleton().signals().game_requested().emit();
+    }
+
+    #[func]
+    fn on_settings_button_pressed(&mut self) {
+        debug!("Settings button pressed");
+        GameSignals::singleton()
+            .signals()
+            .settings_requested()
+            .emit();
+    }
+
+    #[func]
+    fn on_quit_button_pressed(&mut self) {
+        debug!("Quit button pressed");
+        self.leave_run();
+        GameSignals::singleton().signals().menu_requested().emit();
+    }
+
+    /// Unpause and abort the current run, parts reset the time scale when they stop
+    fn leave_run(&mut self) {
+        self.base_mut().set_visible(false);
+        self.base().get_tree().unwrap().set_pause(false);
+        Engine::singleton().set_time_scale(1.0);
+        GameSignals::singleton().signals().game_stopped().emit();
+    }
+}
@@ -0,0 +1,210 @@
+use crate::game_controller::GameState;
+use crate::game_signals::GameSignals;
+use crate::parts::{PART_SCENES, Part, part_title};
+use godot::classes::control::SizeFlags;
+use godot::classes::{Button, Control, GridContainer, HSlider, IControl, Label, OptionButton};
+use godot::prelude::*;
+use log::debug;
+
+/// PracticePanel runs a single part without failures and exposes its parameters as sliders
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct PracticePanel {
+    base: Base<Control>,
+    /// Node whose children are the parts to tune
+    #[export]
+    parts: Option<Gd<Node>>,
+    #[init(node = "%PartOption")]
+    part_option: OnReady<Gd<OptionButton>>,
+    #[init(node = "%Tunables")]
+    tunables: OnReady<Gd<GridContainer>>,
+    #[init(node = "%FailureLog")]
+    failure_log: OnReady<Gd<Label>>,
+    /// Part being practiced, `None` outside the practice mode
+    part_id: Option<i32>,
+    /// Would-be failures since the practice started
+    failures: Vec<GString>,
+}
+
+#[godot_api]
+impl IControl for PracticePanel {
+    fn ready(&mut self) {
+        // Hide the panel until a practice run starts
+        self.base_mut().set_visible(false);
+        GameSignals::singleton()
+            .signals()
+            .practice_requested()
+            .connect_other(self, Self::on_practice_requested);
+        GameSignals::singleton()
+            .signals()
+            .level_selected()
+            .connect_other(self, |this, _level| this.leave_practice());
+        GameSignals::singleton()
+            .signals()
+            .tutorial_requested()
+            .connect_other(self, |this, _step| this.leave_practice());
+        GameSignals::singleton()
+            .signals()
+            .menu_requested()
+            .connect_other(self, Self::leave_practice);
+        GameSignals::singleton()
+            .signals()
+            .game_started()
+            .connect_other(self, Self::show_panel);
+        GameSignals::singleton()
+            .signals()
+            .failure_forgiven()
+            .connect_other(self, Self::on_failure_forgiven);
+        GameSignals::singleton()
+            .signals()
+            .state_changed()
+            .connect_other(self, |this, state| {
+                if state != GameState::Playing && state != GameState::Paused {
+                    this.base_mut().set_visible(false);
+                }
+            });
+
+        for (id, _) in PART_SCENES {
+            let title = part_title(id).unwrap_or_else(|| format!("零件 {}", id).into());
+            self.part_option.add_item(&title);
+        }
+        self.part_option
+            .signals()
+            .item_selected()
+            .connect_other(self, |this, index| {
+                let (id, _) = PART_SCENES[index as usize];
+                this.switch_part(id);
+            });
+
+        // Connect the quit button signal
+        let quit_button = self.base().get_node_as::<Button>("%QuitButton");
+        quit_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_quit_button_pressed);
+    }
+}
+
+#[godot_api]
+impl PracticePanel {
+    /// Rebuild the sliders for the part that was just started and show the panel
+    #[func]
+    pub fn show_panel(&mut self) {
+        let Some(part_id) = self.part_id else {
+            return;
+        };
+        debug!("Showing practice panel for part {}", part_id);
+
+        for mut child in self.tunables.get_children().iter_shared() {
+            child.queue_free();
+        }
+
+        let parts = self
+            .parts
+            .as_ref()
+            .map(|parts| parts.get_children())
+            .unwrap_or_default();
+        for child in parts.iter_shared() {
+            let Ok(part) = child.clone().try_dynify::<dyn Part>() else {
+                continue;
+            };
+            let tunables = part.dyn_bind().tunables();
+            for tunable in tunables {
+                let mut label = Label::new_alloc();
+                label.set_text(tunable.label);
+
+                let value = child
+                    .get(tunable.property)
+                    .try_to::<f64>()
+                    .unwrap_or(tunable.min);
+                let mut value_label = Label::new_alloc();
+                value_label.set_text(&format!("{}", value));
+
+                let mut slider = HSlider::new_alloc();
+                slider.set_min(tunable.min);
+                slider.set_max(tunable.max);
+                slider.set_step(tunable.step);
+                slider.set_value_no_signal(value);
+                slider.set_custom_minimum_size(Vector2::new(160.0, 0.0));
+                slider.set_h_size_flags(SizeFlags::EXPAND_FILL);
+                let property = tunable.property;
+                let mut part_node = child.clone();
+                let mut shown_value = value_label.clone();
+                slider.signals().value_changed().connect(move |value| {
+                    debug!("practice: {} = {}", property, value);
+                    part_node.set(property, &value.to_variant());
+                    shown_value.set_text(&format!("{}", value));
+                });
+
+                self.tunables.add_child(&label);
+                self.tunables.add_child(&slider);
+                self.tunables.add_child(&value_label);
+            }
+        }
+
+        self.base_mut().set_visible(true);
+    }
+
+    #[func]
+    pub fn on_quit_button_pressed(&mut self) {
+        debug!("Quit button pressed");
+        self.base_mut().set_visible(false);
+        let _guard = self.base_mut();
+        GameSignals::singleton().signals().game_stopped().emit();
+        GameSignals::singleton().signals().menu_requested().emit();
+    }
+
+    fn on_practice_requested(&mut self, part_id: i32) {
+        if self.part_id.is_none() {
+            self.failures.clear();
+            self.failure_log.set_text("");
+        }
+        self.part_id = Some(part_id);
+        if let Some(index) = PART_SCENES.iter().position(|(id, _)| *id == part_id) {
+            self.part_option.select(index as i32);
+        }
+        GameSignals::singleton().bind_mut().failures_suppressed = true;
+    }
+
+    /// Log the failure instead of ending the run, then restart the part
+    fn on_failure_forgiven(&mut self, reason: GString) {
+        if self.part_id.is_none() {
+            return;
+        }
+        debug!("practice: would have failed: {}", reason);
+        self.failures.push(reason);
+        let log = self
+            .failures
+            .iter()
+            .rev()
+            .take(5)
+            .map(|reason| format!("差点失败：{}", reason))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.failure_log
+            .set_text(&format!("共 {} 次\n{}", self.failures.len(), log));
+
+        let _guard = self.base_mut();
+        GameSignals::singleton().signals().game_stopped().emit();
+        GameSignals::singleton().signals().game_requested().emit();
+    }
+
+    fn switch_part(&mut self, part_id: i32) {
+        if self.part_id == Some(part_id) {
+            return;
+        }
+        let _guard = self.base_mut();
+        GameSignals::singleton().signals().game_stopped().emit();
+        GameSignals::singleton()
+            .signals()
+            .practice_requested()
+            .emit(part_id);
+    }
+
+    fn leave_practice(&mut self) {
+        if self.part_id.take().is_some() {
+            GameSignals::singleton().bind_mut().failures_suppressed = false;
+        }
+        self.base_mut().set_visible(false);
+    }
+}
@@ -0,0 +1,36 @@
+/*!
+胜利和失败界面共用的本局统计和保存回放按钮
+*/
+
+use crate::game_controller::Game;
+use godot::classes::{Button, Label};
+use godot::prelude::*;
+use log::debug;
+
+/// Fill in the stats of the run that just finished and re-arm the save replay button
+pub fn show_run_stats(
+    game: &Gd<Game>,
+    stats_label: &mut Gd<Label>,
+    save_replay_button: &mut Gd<Button>,
+) {
+    let stats = game
+        .bind()
+        .last_run()
+        .map(|run| run.describe())
+        .unwrap_or_default();
+    stats_label.set_text(&stats);
+    save_replay_button.set_text("保存回放");
+    save_replay_button.set_disabled(false);
+}
+
+/// Save the replay of the last run, the button shows whether it worked
+pub fn save_replay(game: &Gd<Game>, save_replay_button: &mut Gd<Button>) {
+    let path = game.bind().save_replay();
+    if path.is_empty() {
+        save_replay_button.set_text("保存失败");
+        return;
+    }
+    debug!("replay saved to {}", path);
+    save_replay_button.set_text("已保存");
+    save_replay_button.set_disabled(true);
+}
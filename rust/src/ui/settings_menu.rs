@@ -0,0 +1,222 @@
+use crate::game_controller::Difficulty;
+use crate::game_signals::GameSignals;
+use crate::settings::{ColorblindPalette, Settings};
+use godot::classes::{
+    Button, CheckBox, Control, GridContainer, HSlider, IControl, InputEvent, InputEventKey, Label,
+    OptionButton, Os,
+};
+use godot::global::Key;
+use godot::prelude::*;
+use log::debug;
+
+const LANGUAGES: [(&str, &str); 2] = [("zh", "中文"), ("en", "English")];
+const PALETTES: [ColorblindPalette; 4] = [
+    ColorblindPalette::Off,
+    ColorblindPalette::Protanopia,
+    ColorblindPalette::Deuteranopia,
+    ColorblindPalette::Tritanopia,
+];
+
+/// SettingsMenu edits the `Settings` singleton, changes apply at once and are saved on close
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct SettingsMenu {
+    base: Base<Control>,
+    #[init(node = "%LanguageOption")]
+    language_option: OnReady<Gd<OptionButton>>,
+    #[init(node = "%MasterVolumeSlider")]
+    master_volume_slider: OnReady<Gd<HSlider>>,
+    #[init(node = "%SfxVolumeSlider")]
+    sfx_volume_slider: OnReady<Gd<HSlider>>,
+    #[init(node = "%DifficultyOption")]
+    difficulty_option: OnReady<Gd<OptionButton>>,
+    #[init(node = "%PaletteOption")]
+    palette_option: OnReady<Gd<OptionButton>>,
+    #[init(node = "%ReducedMotionCheck")]
+    reduced_motion_check: OnReady<Gd<CheckBox>>,
+    #[init(node = "%KeyBindings")]
+    key_bindings: OnReady<Gd<GridContainer>>,
+    /// Action waiting for a new key and the button showing it
+    rebinding: Option<(&'static str, Gd<Button>)>,
+}
+
+#[godot_api]
+impl IControl for SettingsMenu {
+    fn ready(&mut self) {
+        // Hide the settings menu initially
+        self.base_mut().set_visible(false);
+        GameSignals::singleton()
+            .signals()
+            .settings_requested()
+            .connect_other(self, Self::show_screen);
+
+        for (_, name) in LANGUAGES {
+            self.language_option.add_item(name);
+        }
+        for difficulty in Difficulty::ALL {
+            self.difficulty_option
+                .add_item(&format!("{:?}", difficulty));
+        }
+        for palette in PALETTES {
+            self.palette_option.add_item(&format!("{:?}", palette));
+        }
+
+        for (action, name) in Settings::ACTIONS {
+            let mut label = Label::new_alloc();
+            label.set_text(name);
+            let button = Button::new_alloc();
+            button.signals().pressed().connect_other(self, {
+                let button = button.clone();
+                move |this| this.start_rebinding(action, button.clone())
+            });
+            self.key_bindings.add_child(&label);
+            self.key_bindings.add_child(&button);
+        }
+
+        self.language_option
+            .signals()
+            .item_selected()
+            .connect_other(self, |_this, index| {
+                let mut settings = Settings::singleton();
+                settings.bind_mut().language = LANGUAGES[index as usize].0.into();
+                settings.bind_mut().apply();
+            });
+        self.master_volume_slider
+            .signals()
+            .value_changed()
+            .connect_other(self, |_this, value| {
+                let mut settings = Settings::singleton();
+                settings.bind_mut().master_volume = value;
+                settings.bind_mut().apply();
+            });
+        self.sfx_volume_slider
+            .signals()
+            .value_changed()
+            .connect_other(self, |_this, value| {
+                let mut settings = Settings::singleton();
+                settings.bind_mut().sfx_volume = value;
+                settings.bind_mut().apply();
+            });
+        self.difficulty_option
+            .signals()
+            .item_selected()
+            .connect_other(self, |_this, index| {
+                let mut settings = Settings::singleton();
+                settings.bind_mut().difficulty = Difficulty::ALL[index as usize];
+                settings.bind_mut().apply();
+            });
+        self.palette_option
+            .signals()
+            .item_selected()
+            .connect_other(self, |_this, index| {
+                let mut settings = Settings::singleton();
+                settings.bind_mut().colorblind_palette = PALETTES[index as usize];
+                settings.bind_mut().apply();
+            });
+        self.reduced_motion_check
+            .signals()
+            .toggled()
+            .connect_other(self, |_this, toggled_on| {
+                let mut settings = Settings::singleton();
+                settings.bind_mut().reduced_motion = toggled_on;
+                settings.bind_mut().apply();
+            });
+
+        // Connect the back button signal
+        let back_button = self.base().get_node_as::<Button>("%BackButton");
+        back_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_back_button_pressed);
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        let Some((action, mut button)) = self.rebinding.take() else {
+            return;
+        };
+        let Ok(key_event) = event.try_cast::<InputEventKey>() else {
+            self.rebinding = Some((action, button));
+            return;
+        };
+        if !key_event.is_pressed() || key_event.is_echo() {
+            self.rebinding = Some((action, button));
+            return;
+        }
+
+        let key = key_event.get_physical_keycode();
+        if key != Key::ESCAPE {
+            Settings::singleton().bind_mut().set_key(action, key);
+        }
+        button.set_text(&Self::key_name(action));
+        self.base().get_viewport().unwrap().set_input_as_handled();
+    }
+}
+
+#[godot_api]
+impl SettingsMenu {
+    /// Show the settings menu filled from the current settings
+    #[func]
+    pub fn show_screen(&mut self) {
+        debug!("Showing settings menu");
+        let settings = Settings::singleton();
+        let settings = settings.bind();
+
+        let language = LANGUAGES
+            .iter()
+            .position(|(code, _)| settings.language == GString::from(*code))
+            .unwrap_or_default();
+        self.language_option.select(language as i32);
+        self.master_volume_slider
+            .set_value_no_signal(settings.master_volume);
+        self.sfx_volume_slider
+            .set_value_no_signal(settings.sfx_volume);
+        let difficulty = Difficulty::ALL
+            .iter()
+            .position(|difficulty| *difficulty == settings.difficulty)
+            .unwrap_or_default();
+        self.difficulty_option.select(difficulty as i32);
+        let palette = PALETTES
+            .iter()
+            .position(|palette| *palette == settings.colorblind_palette)
+            .unwrap_or_default();
+        self.palette_option.select(palette as i32);
+        self.reduced_motion_check
+            .set_pressed_no_signal(settings.reduced_motion);
+
+        for (index, (action, _)) in Settings::ACTIONS.iter().enumerate() {
+            // 每个动作占一行：名称标签 + 按键按钮
+            let mut button = self
+                .key_bindings
+                .get_child(index as i32 * 2 + 1)
+                .unwrap()
+                .cast::<Button>();
+            button.set_text(&Self::key_name(action));
+        }
+
+        self.base_mut().set_visible(true);
+    }
+
+    #[func]
+    pub fn on_back_button_pressed(&mut self) {
+        debug!("Back button pressed");
+        self.rebinding = None;
+        Settings::singleton().bind().save();
+        self.base_mut().set_visible(false);
+    }
+
+    /// Wait for the next key press and bind it to `action`, Escape cancels
+    fn start_rebinding(&mut self, action: &'static str, mut button: Gd<Button>) {
+        if let Some((action, mut previous)) = self.rebinding.take() {
+            previous.set_text(&Self::key_name(action));
+        }
+        button.set_text("请按下新按键…");
+        self.rebinding = Some((action, button));
+    }
+
+    fn key_name(action: &str) -> GString {
+        match Settings::key_for(action) {
+            Some(key) => Os::singleton().get_keycode_string(key),
+            None => "未绑定".into(),
+        }
+    }
+}
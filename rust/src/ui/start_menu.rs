@@ -1,6 +1,10 @@
-use godot::prelude::*;
-use godot::classes::{Control, IControl, Button};
+use crate::game_controller::GameState;
 use crate::game_signals::GameSignals;
+use crate::parts::PART_SCENES;
+use crate::tutorial::TutorialProgress;
+use godot::classes::{Button, Control, IControl};
+use godot::prelude::*;
+use log::debug;
 
 /// StartMenu is the initial screen shown to the player
 #[derive(GodotClass)]
@@ -12,9 +16,44 @@ pub struct StartMenu {
 #[godot_api]
 impl IControl for StartMenu {
     fn ready(&mut self) {
+        // The start menu is visible exactly while the game is in the menu state
+        GameSignals::singleton()
+            .signals()
+            .state_changed()
+            .connect_other(self, |this, state| {
+                this.base_mut().set_visible(state == GameState::Menu);
+            });
+
         // Connect the start button signal
         let start_button = self.base().get_node_as::<Button>("StartButton");
-        start_button.signals().pressed().connect_other(self, Self::on_start_button_pressed);
+        start_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_start_button_pressed);
+
+        let tutorial_button = self.base().get_node_as::<Button>("TutorialButton");
+        tutorial_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_tutorial_button_pressed);
+
+        let practice_button = self.base().get_node_as::<Button>("PracticeButton");
+        practice_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_practice_button_pressed);
+
+        let history_button = self.base().get_node_as::<Button>("HistoryButton");
+        history_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_history_button_pressed);
+
+        let settings_button = self.base().get_node_as::<Button>("SettingsButton");
+        settings_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_settings_button_pressed);
     }
 }
 
@@ -22,12 +61,59 @@ impl IControl for StartMenu {
 impl StartMenu {
     #[func]
     fn on_start_button_pressed(&mut self) {
-        godot_print!("Start button pressed");
+        debug!("Start button pressed");
 
-        // Hide the start menu
-        self.base_mut().set_visible(false);
+        // 第一次开始游戏时先进入教程，从上次完成的地方继续
+        let progress = TutorialProgress::load();
+        if !progress.is_completed() {
+            GameSignals::singleton()
+                .signals()
+                .tutorial_requested()
+                .emit(progress.completed_steps);
+            return;
+        }
 
-        // Emit the game started signal
-        GameSignals::singleton().signals().game_started().emit();
+        // Go to the level select, the game state hides this menu
+        GameSignals::singleton()
+            .signals()
+            .level_select_requested()
+            .emit();
+    }
+
+    #[func]
+    fn on_tutorial_button_pressed(&mut self) {
+        debug!("Tutorial button pressed");
+        GameSignals::singleton()
+            .signals()
+            .tutorial_requested()
+            .emit(0);
+    }
+
+    #[func]
+    fn on_practice_button_pressed(&mut self) {
+        debug!("Practice button pressed");
+        let (part_id, _) = PART_SCENES[0];
+        GameSignals::singleton()
+            .signals()
+            .practice_requested()
+            .emit(part_id);
+    }
+
+    #[func]
+    fn on_history_button_pressed(&mut self) {
+        debug!("History button pressed");
+        GameSignals::singleton()
+            .signals()
+            .history_requested()
+            .emit();
+    }
+
+    #[func]
+    fn on_settings_button_pressed(&mut self) {
+        debug!("Settings button pressed");
+        GameSignals::singleton()
+            .signals()
+            .settings_requested()
+            .emit();
     }
 }
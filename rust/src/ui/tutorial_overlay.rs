@@ -0,0 +1,210 @@
+use crate::game_controller::GameState;
+use crate::game_signals::GameSignals;
+use crate::settings::Settings;
+use crate::tutorial::{Goal, STEPS, TutorialProgress};
+use godot::classes::{Button, Control, IControl, Label, Os};
+use godot::prelude::*;
+use log::debug;
+
+/// TutorialOverlay shows the instructions of the current tutorial step and moves through the steps
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct TutorialOverlay {
+    base: Base<Control>,
+    /// Node whose children are the parts to watch
+    #[export]
+    parts: Option<Gd<Node>>,
+    #[init(node = "%InstructionLabel")]
+    instruction_label: OnReady<Gd<Label>>,
+    #[init(node = "%ContinueButton")]
+    continue_button: OnReady<Gd<Button>>,
+    /// Tutorial step being played, `None` outside the tutorial
+    step: Option<i32>,
+    /// Argument the goal signal must carry, for `Goal::SignalWith`
+    expected: bool,
+    demonstrated: bool,
+    /// Why the last attempt failed, shown above the instructions on the retry
+    retry_reason: GString,
+}
+
+#[godot_api]
+impl IControl for TutorialOverlay {
+    fn ready(&mut self) {
+        // Hide the overlay until a tutorial step starts
+        self.base_mut().set_visible(false);
+        GameSignals::singleton()
+            .signals()
+            .tutorial_requested()
+            .connect_other(self, |this, step| {
+                this.step = Some(step);
+                this.retry_reason = GString::new();
+            });
+        GameSignals::singleton()
+            .signals()
+            .level_selected()
+            .connect_other(self, |this, _level| this.leave_tutorial());
+        GameSignals::singleton()
+            .signals()
+            .menu_requested()
+            .connect_other(self, Self::leave_tutorial);
+        GameSignals::singleton()
+            .signals()
+            .countdown_started()
+            .connect_other(self, Self::show_step);
+        GameSignals::singleton()
+            .signals()
+            .failure_forgiven()
+            .connect_other(self, Self::on_failure_forgiven);
+        GameSignals::singleton()
+            .signals()
+            .state_changed()
+            .connect_other(self, |this, state| {
+                let in_run = matches!(
+                    state,
+                    GameState::Countdown | GameState::Playing | GameState::Paused
+                );
+                if !in_run {
+                    this.base_mut().set_visible(false);
+                }
+            });
+
+        self.continue_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_continue_button_pressed);
+    }
+}
+
+#[godot_api]
+impl TutorialOverlay {
+    /// Show the instructions of the step and wait for the player to demonstrate it
+    #[func]
+    pub fn show_step(&mut self) {
+        let Some(step) = self.step else {
+            return;
+        };
+        let Some(config) = STEPS.get(step as usize) else {
+            return;
+        };
+        debug!("Showing tutorial step {}", step);
+
+        let key = match Settings::key_for(config.action) {
+            Some(key) => Os::singleton().get_keycode_string(key).to_string(),
+            None => "未绑定的按键".to_string(),
+        };
+        let mut text = format!("教程 {}/{}\n", step + 1, STEPS.len());
+        if !self.retry_reason.is_empty() {
+            text += &format!("{}，没关系，再试一次\n", self.retry_reason);
+        }
+        text += &config.instructions.replace("{key}", &key);
+        self.instruction_label.set_text(&text);
+        self.continue_button.set_visible(false);
+
+        // 演示之前不会失败
+        self.demonstrated = false;
+        GameSignals::singleton().bind_mut().failures_suppressed = true;
+        self.connect_goal(&config.goal);
+
+        self.base_mut().set_visible(true);
+    }
+
+    #[func]
+    fn on_goal_signal(&mut self) {
+        self.complete_step();
+    }
+
+    #[func]
+    fn on_goal_signal_with(&mut self, value: bool) {
+        if value == self.expected {
+            self.complete_step();
+        }
+    }
+
+    #[func]
+    pub fn on_continue_button_pressed(&mut self) {
+        debug!("Continue button pressed");
+        let Some(step) = self.step else {
+            return;
+        };
+        self.base_mut().set_visible(false);
+
+        // 先结束这一步，再开始下一步
+        let _guard = self.base_mut();
+        GameSignals::singleton().signals().game_stopped().emit();
+        GameSignals::singleton().bind_mut().failures_suppressed = false;
+        if (step as usize + 1) < STEPS.len() {
+            GameSignals::singleton()
+                .signals()
+                .tutorial_requested()
+                .emit(step + 1);
+        } else {
+            GameSignals::singleton().signals().menu_requested().emit();
+        }
+    }
+
+    /// Restart the step after a failure that was suppressed
+    fn on_failure_forgiven(&mut self, reason: GString) {
+        if self.step.is_none() || self.demonstrated {
+            return;
+        }
+        self.retry_reason = reason;
+
+        // 重新开始这一步，倒计时会再次调用 show_step
+        let _guard = self.base_mut();
+        GameSignals::singleton().signals().game_stopped().emit();
+        GameSignals::singleton().signals().game_requested().emit();
+    }
+
+    fn complete_step(&mut self) {
+        let Some(step) = self.step else {
+            return;
+        };
+        if self.demonstrated {
+            return;
+        }
+        self.demonstrated = true;
+        self.retry_reason = GString::new();
+        // 零件还在运行，等玩家点继续时才结束这一步，在那之前仍然不会失败
+        TutorialProgress::complete_step(step);
+
+        let last = step as usize + 1 >= STEPS.len();
+        let text = if last {
+            "做得好！教程完成了"
+        } else {
+            "做得好！"
+        };
+        self.instruction_label.set_text(text);
+        self.continue_button
+            .set_text(if last { "完成" } else { "下一步" });
+        self.continue_button.set_visible(true);
+    }
+
+    /// Listen for the goal signal on the part of this step
+    fn connect_goal(&mut self, goal: &Goal) {
+        let (signal, method) = match goal {
+            Goal::Signal(signal) => (*signal, "on_goal_signal"),
+            Goal::SignalWith(signal, expected) => {
+                self.expected = *expected;
+                (*signal, "on_goal_signal_with")
+            }
+        };
+
+        let parts = self
+            .parts
+            .as_ref()
+            .map(|parts| parts.get_children())
+            .unwrap_or_default();
+        let callable = self.to_gd().callable(method);
+        for mut child in parts.iter_shared() {
+            if child.has_signal(signal) && !child.is_connected(signal, &callable) {
+                child.connect(signal, &callable);
+            }
+        }
+    }
+
+    fn leave_tutorial(&mut self) {
+        self.step = None;
+        GameSignals::singleton().bind_mut().failures_suppressed = false;
+        self.base_mut().set_visible(false);
+    }
+}
@@ -1,12 +1,26 @@
-use godot::prelude::*;
-use godot::classes::{Control, IControl, Button};
+use crate::game_controller::{Game, GameState};
 use crate::game_signals::GameSignals;
+use crate::levels::Level;
+use crate::ui::run_stats;
+use godot::classes::{Button, Control, IControl, Label};
+use godot::prelude::*;
+use log::debug;
 
 /// VictoryScreen is shown when the player wins the game
 #[derive(GodotClass)]
 #[class(init, base=Control)]
 pub struct VictoryScreen {
     base: Base<Control>,
+    #[export]
+    game: Option<Gd<Game>>,
+    #[init(node = "%StatsLabel")]
+    stats_label: OnReady<Gd<Label>>,
+    #[init(node = "%SaveReplayButton")]
+    save_replay_button: OnReady<Gd<Button>>,
+    #[init(node = "NextButton")]
+    next_button: OnReady<Gd<Button>>,
+    /// Level of the run that was just won
+    level: i32,
 }
 
 #[godot_api]
@@ -14,12 +28,51 @@ impl IControl for VictoryScreen {
     fn ready(&mut self) {
         // Hide the victory screen initially
         self.base_mut().set_visible(false);
-        GameSignals::singleton().signals().game_victory().connect_other(self, Self::show_screen);
+        GameSignals::singleton()
+            .signals()
+            .game_victory()
+            .connect_other(self, Self::show_screen);
+        GameSignals::singleton()
+            .signals()
+            .level_loaded()
+            .connect_other(self, |this, level| this.level = level);
+        GameSignals::singleton()
+            .signals()
+            .state_changed()
+            .connect_other(self, |this, state| {
+                if state != GameState::Result {
+                    this.base_mut().set_visible(false);
+                }
+            });
 
         // Connect the restart button signal
         let restart_button = self.base().get_node_as::<Button>("RestartButton");
 
-        restart_button.signals().pressed().connect_other(self, Self::on_restart_button_pressed);
+        restart_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_restart_button_pressed);
+
+        self.next_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_next_button_pressed);
+
+        let menu_button = self.base().get_node_as::<Button>("MenuButton");
+        menu_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_menu_button_pressed);
+
+        self.save_replay_button
+            .signals()
+            .pressed()
+            .connect_other(self, Self::on_save_replay_button_pressed);
+        if let Some(game) = &self.game {
+            game.signals()
+                .run_finished()
+                .connect_other(self, Self::on_run_finished);
+        }
     }
 }
 
@@ -28,16 +81,50 @@ impl VictoryScreen {
     /// Show the victory screen
     #[func]
     pub fn show_screen(&mut self) {
-        godot_print!("Showing victory screen");
+        debug!("Showing victory screen");
+        let has_next = Level::get(self.level).is_some_and(|level| !level.is_last());
+        self.next_button.set_visible(has_next);
         self.base_mut().set_visible(true);
     }
 
     #[func]
     pub fn on_restart_button_pressed(&mut self) {
-        godot_print!("Restart button pressed");
+        debug!("Restart button pressed");
+        self.base_mut().set_visible(false);
+
+        // We'll request a new run to restart
+        GameSignals::singleton().signals().game_requested().emit();
+    }
+
+    #[func]
+    pub fn on_next_button_pressed(&mut self) {
+        debug!("Next level button pressed");
         self.base_mut().set_visible(false);
+        GameSignals::singleton()
+            .signals()
+            .next_level_requested()
+            .emit();
+    }
 
-        // We'll emit the game started signal to restart
-        GameSignals::singleton().signals().game_started().emit();
+    #[func]
+    pub fn on_menu_button_pressed(&mut self) {
+        debug!("Menu button pressed");
+        self.base_mut().set_visible(false);
+        GameSignals::singleton().signals().menu_requested().emit();
+    }
+
+    /// Fill in the stats of the run that just finished
+    fn on_run_finished(&mut self) {
+        if let Some(game) = &self.game {
+            run_stats::show_run_stats(game, &mut self.stats_label, &mut self.save_replay_button);
+        }
+    }
+
+    #[func]
+    pub fn on_save_replay_button_pressed(&mut self) {
+        debug!("Save replay button pressed");
+        if let Some(game) = &self.game {
+            run_stats::save_replay(game, &mut self.save_replay_button);
+        }
     }
 }